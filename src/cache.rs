@@ -0,0 +1,278 @@
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::config;
+
+// (method, args, block_height)
+type CacheKey = (&'static str, String, u64);
+type CacheValue = Arc<dyn Any + Send + Sync>;
+
+#[derive(Default)]
+struct Entries {
+    values: HashMap<CacheKey, CacheValue>,
+    // The oldest keys go first, we drop them when the cache is full
+    keys: VecDeque<CacheKey>,
+}
+
+/// The results which never change: the data at the blocks which are old enough.
+/// The server uses the global instance configured by `init`, the tests could create their own one
+pub(crate) struct ResponseCache {
+    capacity: AtomicUsize,
+    finality_blocks: AtomicU64,
+    // The latest block we've seen in the DB, all the requests to this block are "latest" ones
+    latest_block_height: AtomicU64,
+    entries: Mutex<Entries>,
+}
+
+static CACHE: Lazy<ResponseCache> =
+    Lazy::new(|| ResponseCache::new(&config::CacheConfig::default()));
+
+static CACHE_HITS: Lazy<prometheus::IntCounterVec> = Lazy::new(|| {
+    prometheus::IntCounterVec::new(
+        prometheus::Opts::new(
+            "api_cache_hits_total",
+            "Number of responses taken from the cache",
+        ),
+        &["method"],
+    )
+    .expect("Failed to create api_cache_hits_total metric")
+});
+static CACHE_MISSES: Lazy<prometheus::IntCounterVec> = Lazy::new(|| {
+    prometheus::IntCounterVec::new(
+        prometheus::Opts::new(
+            "api_cache_misses_total",
+            "Number of cacheable responses which were not found in the cache",
+        ),
+        &["method"],
+    )
+    .expect("Failed to create api_cache_misses_total metric")
+});
+
+/// Should be called once at startup, before the server starts
+pub(crate) fn init(
+    cache_config: &config::CacheConfig,
+    registry: &prometheus::Registry,
+) -> prometheus::Result<()> {
+    CACHE.configure(cache_config);
+    registry.register(Box::new(CACHE_HITS.clone()))?;
+    registry.register(Box::new(CACHE_MISSES.clone()))
+}
+
+/// The instance used by the server
+pub(crate) fn get_global() -> &'static ResponseCache {
+    &CACHE
+}
+
+pub(crate) fn update_latest_block_height(block_height: u64) {
+    CACHE.update_latest_block_height(block_height)
+}
+
+/// 0 if we have not seen any block yet
+pub(crate) fn get_latest_block_height() -> u64 {
+    CACHE.get_latest_block_height()
+}
+
+/// Returns the cached result for (method, args, block_height) if any, otherwise computes and caches it.
+/// See `ResponseCache::cached`
+pub(crate) async fn cached<T, F>(
+    method: &'static str,
+    args: String,
+    block_height: u64,
+    f: F,
+) -> crate::Result<T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: std::future::Future<Output = crate::Result<T>>,
+{
+    CACHE.cached(method, args, block_height, f).await
+}
+
+impl ResponseCache {
+    pub(crate) fn new(cache_config: &config::CacheConfig) -> Self {
+        Self {
+            capacity: AtomicUsize::new(cache_config.capacity),
+            finality_blocks: AtomicU64::new(cache_config.finality_blocks),
+            latest_block_height: AtomicU64::new(0),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    fn configure(&self, cache_config: &config::CacheConfig) {
+        self.capacity
+            .store(cache_config.capacity, Ordering::Relaxed);
+        self.finality_blocks
+            .store(cache_config.finality_blocks, Ordering::Relaxed);
+    }
+
+    pub(crate) fn update_latest_block_height(&self, block_height: u64) {
+        self.latest_block_height
+            .fetch_max(block_height, Ordering::Relaxed);
+    }
+
+    /// 0 if we have not seen any block yet
+    pub(crate) fn get_latest_block_height(&self) -> u64 {
+        self.latest_block_height.load(Ordering::Relaxed)
+    }
+
+    /// The data at the block never changes when the block is old enough,
+    /// so that all the indexers have already written it.
+    /// The requests to the latest blocks are not cached
+    pub(crate) fn is_cacheable(&self, block_height: u64) -> bool {
+        block_height.saturating_add(self.finality_blocks.load(Ordering::Relaxed))
+            < self.get_latest_block_height()
+    }
+
+    /// Returns the cached result for (method, args, block_height) if any, otherwise computes and caches it.
+    /// `args` should contain everything which affects the result except the block.
+    /// We cache successful results and the errors which could not be fixed by retrying
+    pub(crate) async fn cached<T, F>(
+        &self,
+        method: &'static str,
+        args: String,
+        block_height: u64,
+        f: F,
+    ) -> crate::Result<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: std::future::Future<Output = crate::Result<T>>,
+    {
+        if !self.is_cacheable(block_height) {
+            return f.await;
+        }
+        let key: CacheKey = (method, args, block_height);
+        let cached_value = self.entries.lock().unwrap().values.get(&key).cloned();
+        if let Some(result) = cached_value
+            .as_ref()
+            .and_then(|value| value.downcast_ref::<crate::Result<T>>())
+        {
+            CACHE_HITS.with_label_values(&[method]).inc();
+            return result.clone();
+        }
+
+        CACHE_MISSES.with_label_values(&[method]).inc();
+        let result = f.await;
+        if result.as_ref().map_or_else(|err| !err.retriable, |_| true) {
+            self.insert(key, Arc::new(result.clone()));
+        }
+        result
+    }
+
+    fn insert(&self, key: CacheKey, value: CacheValue) {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        let mut entries = self.entries.lock().unwrap();
+        if capacity == 0 || entries.values.contains_key(&key) {
+            return;
+        }
+        while entries.keys.len() >= capacity {
+            match entries.keys.pop_front() {
+                Some(oldest_key) => {
+                    entries.values.remove(&oldest_key);
+                }
+                None => break,
+            }
+        }
+        entries.keys.push_back(key.clone());
+        entries.values.insert(key, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors;
+
+    fn get_cache() -> ResponseCache {
+        let cache = ResponseCache::new(&config::CacheConfig::default());
+        cache.update_latest_block_height(1000);
+        cache
+    }
+
+    async fn get_value(
+        cache: &ResponseCache,
+        block_height: u64,
+        calls: &AtomicU64,
+    ) -> crate::Result<u64> {
+        cache
+            .cached("test", "args".to_string(), block_height, async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(block_height)
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_cached_old_block() {
+        let cache = get_cache();
+        let calls = AtomicU64::new(0);
+        assert_eq!(get_value(&cache, 100, &calls).await.unwrap(), 100);
+        assert_eq!(get_value(&cache, 100, &calls).await.unwrap(), 100);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert_eq!(get_value(&cache, 200, &calls).await.unwrap(), 200);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_latest_block_bypassed() {
+        let cache = get_cache();
+        let calls = AtomicU64::new(0);
+        // within `finality_blocks` from the latest block
+        get_value(&cache, 990, &calls).await.unwrap();
+        get_value(&cache, 990, &calls).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_no_blocks_seen() {
+        let cache = ResponseCache::new(&config::CacheConfig::default());
+        let calls = AtomicU64::new(0);
+        get_value(&cache, 100, &calls).await.unwrap();
+        get_value(&cache, 100, &calls).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_capacity() {
+        let cache = ResponseCache::new(&config::CacheConfig {
+            capacity: 1,
+            ..Default::default()
+        });
+        cache.update_latest_block_height(1000);
+        let calls = AtomicU64::new(0);
+        get_value(&cache, 100, &calls).await.unwrap();
+        get_value(&cache, 200, &calls).await.unwrap();
+        get_value(&cache, 100, &calls).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cached_errors() {
+        let cache = get_cache();
+        let calls = AtomicU64::new(0);
+        for _ in 0..2 {
+            let result: crate::Result<u64> = cache
+                .cached("test_errors", "retriable".to_string(), 300, async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err(errors::ErrorKind::RPCError("timeout".to_string()).into())
+                })
+                .await;
+            assert!(result.is_err());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        for _ in 0..2 {
+            let result: crate::Result<u64> = cache
+                .cached("test_errors", "invalid".to_string(), 300, async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err(errors::ErrorKind::InvalidInput("no such account".to_string()).into())
+                })
+                .await;
+            assert!(result.is_err());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -1,9 +1,47 @@
+const CONFIG_PATH_ENV: &str = "CONFIG_PATH";
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+// NEAR_ENHANCED_API__CACHE__CAPACITY=100 sets `cache.capacity`
+const ENV_PREFIX: &str = "NEAR_ENHANCED_API__";
+const ENV_SEPARATOR: &str = "__";
+// The env variables we had before the config file, with the config keys they set
+const LEGACY_ENV_VARIABLES: &[(&str, &str)] = &[
+    ("EXPLORER_DATABASE_URL", "database.explorer_url"),
+    ("BALANCES_DATABASE_URL", "database.balances_url"),
+    ("DATABASE_MAX_CONNECTIONS", "database.max_connections"),
+    ("RPC_URL", "rpc.url"),
+    ("API_BASE_PATH", "api.base_path"),
+    ("API_SPEC_BASE_PATH", "api.spec_base_path"),
+    ("API_SERVER_PUBLIC_HOST", "api.public_host"),
+];
+
+#[derive(Debug, derive_more::Display)]
+pub enum ConfigError {
+    #[display(fmt = "Failed to read config file {}: {}", _0, _1)]
+    Read(String, String),
+    #[display(fmt = "Failed to parse config: {}", _0)]
+    Parse(String),
+    #[display(fmt = "Invalid config: {}", _0)]
+    Invalid(String),
+}
+
+impl std::error::Error for ConfigError {}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub addr: String,
     pub cors_allowed_origins: Vec<String>,
-    #[serde(default)]
+    pub database: DatabaseConfig,
+    pub rpc: RpcConfig,
+    pub api: ApiConfig,
     pub limits: LimitsConfig,
+    pub nft_reference: NftReferenceConfig,
+    pub nft_gateways: NftGatewaysConfig,
+    pub cache: CacheConfig,
+    pub http_cache: HttpCacheConfig,
+    pub status: StatusConfig,
+    pub health: HealthConfig,
+    pub deadline: DeadlineConfig,
 }
 
 impl Default for Config {
@@ -11,20 +49,532 @@ impl Default for Config {
         Self {
             addr: "0.0.0.0:3050".to_owned(),
             cors_allowed_origins: vec!["*".to_owned()],
+            database: DatabaseConfig::default(),
+            rpc: RpcConfig::default(),
+            api: ApiConfig::default(),
             limits: LimitsConfig::default(),
+            nft_reference: NftReferenceConfig::default(),
+            nft_gateways: NftGatewaysConfig::default(),
+            cache: CacheConfig::default(),
+            http_cache: HttpCacheConfig::default(),
+            status: StatusConfig::default(),
+            health: HealthConfig::default(),
+            deadline: DeadlineConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads TOML file from `CONFIG_PATH` (`config.toml` by default, it's optional),
+    /// applies the overrides from env variables and validates the result.
+    /// Env variables are taken as strings for the string fields and parsed as TOML values otherwise,
+    /// the ones which could not be parsed are taken as strings
+    pub fn load() -> Result<Self, ConfigError> {
+        let content = match std::env::var(CONFIG_PATH_ENV) {
+            Ok(path) => Some(
+                std::fs::read_to_string(&path)
+                    .map_err(|err| ConfigError::Read(path, err.to_string()))?,
+            ),
+            Err(_) => match std::fs::read_to_string(DEFAULT_CONFIG_PATH) {
+                Ok(content) => Some(content),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => {
+                    return Err(ConfigError::Read(
+                        DEFAULT_CONFIG_PATH.to_string(),
+                        err.to_string(),
+                    ))
+                }
+            },
+        };
+        let env: Vec<(String, String)> = std::env::vars().collect();
+        Self::from_sources(content.as_deref(), &env)
+    }
+
+    fn from_sources(content: Option<&str>, env: &[(String, String)]) -> Result<Self, ConfigError> {
+        // The env values are parsed by the type of the field, e.g. `2024` stays a string
+        // for a string field. The fields without default values have no known type,
+        // so if the typed values do not fit, we try them as strings
+        let config = match Self::merge_sources(content, env, false)?.try_into::<Self>() {
+            Ok(config) => config,
+            Err(err) => Self::merge_sources(content, env, true)?
+                .try_into::<Self>()
+                .map_err(|_| ConfigError::Parse(err.to_string()))?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn merge_sources(
+        content: Option<&str>,
+        env: &[(String, String)],
+        untyped_as_strings: bool,
+    ) -> Result<toml::Value, ConfigError> {
+        let defaults = toml::Value::try_from(Self::default())
+            .map_err(|err| ConfigError::Parse(err.to_string()))?;
+        let mut value = match content {
+            Some(content) => content
+                .parse::<toml::Value>()
+                .map_err(|err| ConfigError::Parse(err.to_string()))?,
+            None => toml::Value::Table(toml::value::Table::new()),
+        };
+        let legacy_env = LEGACY_ENV_VARIABLES.iter().filter_map(|(env_name, key)| {
+            env.iter()
+                .find(|(name, _)| name == env_name)
+                .map(|(_, env_value)| (key.to_string(), env_value))
+        });
+        let prefixed_env = env.iter().filter_map(|(env_name, env_value)| {
+            env_name
+                .strip_prefix(ENV_PREFIX)
+                .map(|key| (key.to_lowercase().replace(ENV_SEPARATOR, "."), env_value))
+        });
+        for (key, env_value) in legacy_env.chain(prefixed_env) {
+            let env_value = match get_value(&defaults, &key) {
+                Some(toml::Value::String(_)) => toml::Value::String(env_value.to_string()),
+                None if untyped_as_strings => toml::Value::String(env_value.to_string()),
+                _ => parse_env_value(env_value),
+            };
+            set_value(&mut value, &key, env_value)?;
+        }
+        Ok(value)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let checks = [
+            (self.addr.is_empty(), "addr should not be empty"),
+            (
+                self.cors_allowed_origins.is_empty(),
+                "cors_allowed_origins should not be empty, use [\"*\"] to allow all",
+            ),
+            (
+                self.database.explorer_url.is_empty(),
+                "database.explorer_url (or EXPLORER_DATABASE_URL) is required",
+            ),
+            (
+                self.database.balances_url.is_empty(),
+                "database.balances_url (or BALANCES_DATABASE_URL) is required",
+            ),
+            (
+                self.database.max_connections == 0,
+                "database.max_connections should be positive",
+            ),
+            (
+                self.database.retry_count == 0,
+                "database.retry_count should be positive",
+            ),
+            (
+                self.rpc.url.is_empty() && self.rpc.endpoints.is_empty(),
+                "rpc.url (or RPC_URL) or rpc.endpoints is required",
+            ),
+            (
+                self.rpc
+                    .endpoints
+                    .iter()
+                    .any(|endpoint| endpoint.url.is_empty()),
+                "rpc.endpoints should have url",
+            ),
+            (
+                self.rpc.retry_count == 0,
+                "rpc.retry_count should be positive",
+            ),
+            (
+                self.rpc.timeout_ms == 0,
+                "rpc.timeout_ms should be positive",
+            ),
+            (
+                self.limits.default_page_limit == 0
+                    || self.limits.default_page_limit > self.limits.max_page_limit,
+                "limits.default_page_limit should be in range [1, limits.max_page_limit]",
+            ),
+            (
+                self.status.refresh_interval_ms == 0,
+                "status.refresh_interval_ms should be positive",
+            ),
+            (
+                self.health.timeout_ms == 0,
+                "health.timeout_ms should be positive",
+            ),
+            (
+                self.deadline.timeout_ms == 0
+                    || self.deadline.timeout_ms > self.deadline.max_timeout_ms,
+                "deadline.timeout_ms should be in range [1, deadline.max_timeout_ms]",
+            ),
+        ];
+        match checks.iter().find(|(is_invalid, _)| *is_invalid) {
+            Some((_, message)) => Err(ConfigError::Invalid(message.to_string())),
+            None => Ok(()),
         }
     }
 }
 
+fn parse_env_value(env_value: &str) -> toml::Value {
+    format!("value = {}", env_value)
+        .parse::<toml::Value>()
+        .ok()
+        .and_then(|table| table.get("value").cloned())
+        .unwrap_or_else(|| toml::Value::String(env_value.to_string()))
+}
+
+// Gets `a.b.c` key
+fn get_value<'a>(config: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.').try_fold(config, |value, key| value.get(key))
+}
+
+// Sets `a.b.c` key, creates the tables on the way if needed
+fn set_value(config: &mut toml::Value, key: &str, value: toml::Value) -> Result<(), ConfigError> {
+    let table = config
+        .as_table_mut()
+        .ok_or_else(|| ConfigError::Invalid(format!("Could not set {}", key)))?;
+    match key.split_once('.') {
+        None => {
+            table.insert(key.to_string(), value);
+            Ok(())
+        }
+        Some((head, rest)) => set_value(
+            table
+                .entry(head.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::value::Table::new())),
+            rest,
+            value,
+        ),
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub explorer_url: String,
+    pub balances_url: String,
+    /// See https://docs.rs/sqlx/latest/sqlx/struct.Pool.html#2-connection-limits-mysql-mssql-postgres
+    pub max_connections: u32,
+    /// How long we wait for the free connection in the pool
+    pub acquire_timeout_ms: u64,
+    /// The replicas cancel the queries while applying WALs, so we try the query several times
+    pub retry_count: usize,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            explorer_url: String::new(),
+            balances_url: String::new(),
+            max_connections: 97,
+            acquire_timeout_ms: 30_000,
+            retry_count: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RpcConfig {
+    /// The archival node we use if `endpoints` are not set
+    pub url: String,
+    pub endpoints: Vec<RpcEndpointConfig>,
+    pub retry_count: usize,
+    /// The requests to the blocks older than this go only to the archival nodes.
+    /// The regular nodes keep 5 epochs of data, 43200 blocks each
+    pub archival_threshold_blocks: u64,
+    /// The endpoint is skipped for `unhealthy_cooldown_ms` after this number of failures in a row
+    pub unhealthy_after_failures: u32,
+    pub unhealthy_cooldown_ms: u64,
+    /// How long we wait for the answer of one endpoint before going to the next one
+    pub timeout_ms: u64,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            endpoints: vec![],
+            retry_count: 10,
+            archival_threshold_blocks: 200_000,
+            unhealthy_after_failures: 3,
+            unhealthy_cooldown_ms: 30_000,
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RpcEndpointConfig {
+    pub url: String,
+    #[serde(default)]
+    pub archival: bool,
+}
+
+/// The paths and host we show in the API spec
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub base_path: String,
+    pub spec_base_path: String,
+    pub public_host: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct LimitsConfig {
     pub input_payload_max_size: usize,
+    pub default_page_limit: u32,
+    pub max_page_limit: u32,
 }
 
 impl Default for LimitsConfig {
     fn default() -> Self {
         Self {
             input_payload_max_size: 10 * 1024 * 1024,
+            default_page_limit: 20,
+            max_page_limit: 100,
+        }
+    }
+}
+
+/// The budget for loading off-chain NFT reference JSON
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct NftReferenceConfig {
+    pub max_size_bytes: usize,
+    pub timeout_ms: u64,
+    /// The number of the documents we keep in memory
+    pub cache_capacity: usize,
+    /// For how long we do not try to load the reference again after the failure
+    pub failure_cache_ttl_ms: u64,
+}
+
+impl Default for NftReferenceConfig {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: 256 * 1024,
+            timeout_ms: 3000,
+            cache_capacity: 1000,
+            failure_cache_ttl_ms: 60_000,
+        }
+    }
+}
+
+/// The gateways we use to build HTTP URLs for the content-addressed NFT media and references
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct NftGatewaysConfig {
+    pub ipfs_gateway: String,
+    pub arweave_gateway: String,
+}
+
+impl Default for NftGatewaysConfig {
+    fn default() -> Self {
+        Self {
+            ipfs_gateway: "https://ipfs.io/ipfs/".to_owned(),
+            arweave_gateway: "https://arweave.net/".to_owned(),
+        }
+    }
+}
+
+/// The cache for the requests to the old blocks, the data there never changes
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// The number of the cached responses, 0 disables the cache
+    pub capacity: usize,
+    /// The block is cached only if there are more than `finality_blocks` blocks after it,
+    /// so that all the indexers have already written its data
+    pub finality_blocks: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            finality_blocks: 20,
+        }
+    }
+}
+
+/// `Cache-Control` headers we give to the clients and CDN
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HttpCacheConfig {
+    /// For the requests without the explicit block, the data changes with each new block
+    pub latest_max_age_seconds: u64,
+    /// For the requests to the old blocks, the data never changes
+    pub pinned_max_age_seconds: u64,
+}
+
+impl Default for HttpCacheConfig {
+    fn default() -> Self {
+        Self {
+            latest_max_age_seconds: 1,
+            pinned_max_age_seconds: 365 * 24 * 60 * 60,
+        }
+    }
+}
+
+/// Indexer lag monitoring
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct StatusConfig {
+    /// The service is degraded if the indexed data is behind RPC for more than this
+    pub max_lag_seconds: u64,
+    /// How often we refresh the lag we show in `x-indexer-lag-seconds` header
+    pub refresh_interval_ms: u64,
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            max_lag_seconds: 60,
+            refresh_interval_ms: 5000,
         }
     }
 }
+
+/// `/health/ready` probe
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Each dependency should answer in this time to be considered healthy
+    pub timeout_ms: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self { timeout_ms: 1000 }
+    }
+}
+
+/// The time budget of each request, DB and RPC retries stop when it's exhausted
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DeadlineConfig {
+    pub timeout_ms: u64,
+    /// The client could ask for the other timeout with `x-request-timeout-ms` header, up to this value
+    pub max_timeout_ms: u64,
+}
+
+impl Default for DeadlineConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 30_000,
+            max_timeout_ms: 120_000,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_env(variables: &[(&str, &str)]) -> Vec<(String, String)> {
+        variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_config_from_file_and_env() {
+        let content = r#"
+            addr = "127.0.0.1:8080"
+
+            [database]
+            explorer_url = "postgres://explorer"
+            balances_url = "postgres://balances"
+            max_connections = 10
+
+            [cache]
+            capacity = 5
+        "#;
+        let env = get_env(&[
+            ("RPC_URL", "https://rpc.mainnet.near.org"),
+            ("DATABASE_MAX_CONNECTIONS", "20"),
+            ("NEAR_ENHANCED_API__CACHE__FINALITY_BLOCKS", "3"),
+            ("NEAR_ENHANCED_API__API__BASE_PATH", "/v1"),
+            (
+                "NEAR_ENHANCED_API__CORS_ALLOWED_ORIGINS",
+                r#"["https://example.com"]"#,
+            ),
+            ("UNRELATED", "1"),
+        ]);
+        let config = Config::from_sources(Some(content), &env).unwrap();
+        assert_eq!(config.addr, "127.0.0.1:8080");
+        assert_eq!(config.cors_allowed_origins, vec!["https://example.com"]);
+        assert_eq!(config.database.explorer_url, "postgres://explorer");
+        assert_eq!(config.database.max_connections, 20);
+        assert_eq!(config.database.retry_count, 2);
+        assert_eq!(config.rpc.url, "https://rpc.mainnet.near.org");
+        assert_eq!(config.api.base_path, "/v1");
+        assert_eq!(config.cache.capacity, 5);
+        assert_eq!(config.cache.finality_blocks, 3);
+        assert_eq!(config.limits.max_page_limit, 100);
+    }
+
+    #[test]
+    fn test_config_string_env_values() {
+        let env = get_env(&[
+            ("EXPLORER_DATABASE_URL", "postgres://explorer"),
+            ("BALANCES_DATABASE_URL", "postgres://balances"),
+            ("RPC_URL", "https://rpc.mainnet.near.org"),
+            ("NEAR_ENHANCED_API__API__BASE_PATH", "2024"),
+            ("NEAR_ENHANCED_API__API__SPEC_BASE_PATH", "true"),
+            ("NEAR_ENHANCED_API__API__PUBLIC_HOST", "1234"),
+            ("NEAR_ENHANCED_API__CACHE__CAPACITY", "7"),
+        ]);
+        let config = Config::from_sources(None, &env).unwrap();
+        assert_eq!(config.api.base_path, "2024");
+        assert_eq!(config.api.spec_base_path, "true");
+        assert_eq!(config.api.public_host.as_deref(), Some("1234"));
+        assert_eq!(config.cache.capacity, 7);
+    }
+
+    #[test]
+    fn test_config_rpc_endpoints() {
+        let content = r#"
+            [database]
+            explorer_url = "postgres://explorer"
+            balances_url = "postgres://balances"
+
+            [[rpc.endpoints]]
+            url = "https://rpc.mainnet.near.org"
+
+            [[rpc.endpoints]]
+            url = "https://archival-rpc.mainnet.near.org"
+            archival = true
+        "#;
+        let config = Config::from_sources(Some(content), &[]).unwrap();
+        assert!(config.rpc.url.is_empty());
+        assert_eq!(config.rpc.endpoints.len(), 2);
+        assert!(!config.rpc.endpoints[0].archival);
+        assert!(config.rpc.endpoints[1].archival);
+        assert_eq!(config.rpc.unhealthy_after_failures, 3);
+        assert_eq!(config.rpc.timeout_ms, 10_000);
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let env = get_env(&[
+            ("EXPLORER_DATABASE_URL", "postgres://explorer"),
+            ("BALANCES_DATABASE_URL", "postgres://balances"),
+        ]);
+        assert!(matches!(
+            Config::from_sources(None, &env),
+            Err(ConfigError::Invalid(message)) if message.contains("rpc.url")
+        ));
+        assert!(matches!(
+            Config::from_sources(Some("addr = "), &env),
+            Err(ConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            Config::from_sources(Some("[limits]\ndefault_page_limit = \"many\""), &env),
+            Err(ConfigError::Parse(_))
+        ));
+
+        let mut env = env;
+        env.extend(get_env(&[
+            ("RPC_URL", "https://rpc.mainnet.near.org"),
+            ("NEAR_ENHANCED_API__LIMITS__DEFAULT_PAGE_LIMIT", "500"),
+        ]));
+        assert!(matches!(
+            Config::from_sources(None, &env),
+            Err(ConfigError::Invalid(message)) if message.contains("default_page_limit")
+        ));
+    }
+}
@@ -1,17 +1,27 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use once_cell::sync::Lazy;
 use sqlx::{postgres::PgRow, Arguments};
 
-use crate::{errors, types, BigDecimal};
+use crate::{cache, config, deadline, errors, rpc_helpers, types, BigDecimal};
 
 // The DB replicas apply the WALs each X seconds (X=30 or 300 in our case, depend on replica).
 // If the SELECT query started right before WAL started to apply, the query is cancelled.
 // That's why we need to try the second time.
 // If it hits the limit again, it makes to sense to try run it the third time,
-// 99% we will hit the limit again, that's why we have 2 here
-const DB_RETRY_COUNT: usize = 2;
+// 99% we will hit the limit again, that's why we have 2 by default
+static DB_RETRY_COUNT: Lazy<AtomicUsize> =
+    Lazy::new(|| AtomicUsize::new(config::DatabaseConfig::default().retry_count));
 
 const INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
 const MAX_DELAY_TIME: std::time::Duration = std::time::Duration::from_secs(120);
 
+/// Should be called once at startup, before the server starts
+pub(crate) fn init(database_config: &config::DatabaseConfig) {
+    DB_RETRY_COUNT.store(database_config.retry_count, Ordering::Relaxed);
+}
+
 pub struct ExplorerPool(pub sqlx::Pool<sqlx::Postgres>);
 pub struct BalancesPool(pub sqlx::Pool<sqlx::Postgres>);
 
@@ -26,10 +36,26 @@ pub(crate) struct AccountId {
     pub account_id: String,
 }
 
+#[derive(sqlx::FromRow)]
+struct TransactionInfo {
+    pub receipt_id: String,
+    pub transaction_hash: String,
+    pub signer_account_id: String,
+}
+
+/// The receipt of the history item and the transaction which produced it
+#[derive(Debug, Default)]
+pub(crate) struct ReceiptTransaction {
+    pub receipt_id: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub signer_account_id: Option<types::AccountId>,
+}
+
 #[derive(Debug)]
 pub(crate) struct Block {
     pub timestamp: u64,
     pub height: u64,
+    pub finality: types::query_params::Finality,
 }
 
 impl TryFrom<&BlockView> for Block {
@@ -39,83 +65,167 @@ impl TryFrom<&BlockView> for Block {
         Ok(Self {
             timestamp: types::numeric::to_u64(&block.block_timestamp)?,
             height: types::numeric::to_u64(&block.block_height)?,
+            finality: types::query_params::Finality::default(),
         })
     }
 }
 
-pub(crate) fn timestamp_to_event_index(timestamp: u64) -> u128 {
-    timestamp as u128 * 100_000_000 * 100_000_000
-}
-
-pub(crate) fn event_index_to_timestamp(event_index: u128) -> u64 {
-    (event_index / (100_000_000 * 100_000_000)) as u64
-}
-
 pub(crate) async fn get_block_from_pagination(
     pool_explorer: &ExplorerPool,
     pagination: &types::query_params::Pagination,
 ) -> crate::Result<Block> {
-    checked_get_block(
-        pool_explorer,
-        &types::query_params::BlockParams {
-            block_timestamp_nanos: pagination
-                .after_event_index
-                .map(|event_index| event_index_to_timestamp(event_index).into()),
-            block_height: None,
-        },
-    )
-    .await
+    match pagination.after_event_index {
+        Some(event_index) => {
+            get_block_by_timestamp(
+                pool_explorer,
+                types::EventIndex::new(event_index).timestamp_nanos(),
+                None,
+            )
+            .await
+        }
+        None => get_last_block(pool_explorer).await,
+    }
 }
 
-/// Validates block_params received from the user, sets the default value if none was provided
+/// Validates block_params received from the user, sets the default value if none was provided.
+/// With `final` finality, we never give the blocks after the latest final block known by RPC
 pub(crate) async fn checked_get_block(
     pool_explorer: &ExplorerPool,
+    rpc_client: &crate::rpc_pool::RpcPool,
     block_params: &types::query_params::BlockParams,
 ) -> crate::Result<Block> {
-    if block_params.block_height.is_some() && block_params.block_timestamp_nanos.is_some() {
-        return Err(errors::ErrorKind::InvalidInput(
-            "Both block_height and block_timestamp_nanos found. Please provide only one of values"
-                .to_string(),
-        )
+    let block_selectors: Vec<&str> = [
+        ("block_height", block_params.block_height.is_some()),
+        (
+            "block_timestamp_nanos",
+            block_params.block_timestamp_nanos.is_some(),
+        ),
+        ("block_hash", block_params.block_hash.is_some()),
+        ("at", block_params.at.is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, is_provided)| *is_provided)
+    .map(|(name, _)| name)
+    .collect();
+    if block_selectors.len() > 1 {
+        return Err(errors::ErrorKind::InvalidInput(format!(
+            "{} found. Please provide only one of values",
+            block_selectors.join(", ")
+        ))
         .into());
     }
+    let at_timestamp = match &block_params.at {
+        Some(at) => Some(types::timestamp::parse_iso_to_nanos(at).ok_or_else(|| {
+            errors::ErrorKind::InvalidInput(format!("at {} is not a valid ISO 8601 datetime", at))
+        })?),
+        None => None,
+    };
 
-    if let Some(block_height) = block_params.block_height {
-        match select_retry_or_panic::<BlockView>(
-            &pool_explorer.0,
-            r"SELECT block_height, block_timestamp
-              FROM blocks
-              WHERE block_height = $1::numeric(20, 0)",
-            &[block_height.0.to_string()],
-        )
-        .await?
-        .first()
-        {
-            None => Err(errors::ErrorKind::InvalidInput(format!(
-                "block_height {} is not found",
-                block_height.0
-            ))
-            .into()),
-            Some(block) => Ok(Block::try_from(block)?),
-        }
-    } else if let Some(block_timestamp) = block_params.block_timestamp_nanos {
-        match select_retry_or_panic::<BlockView>(
-            &pool_explorer.0,
-            r"SELECT block_height, block_timestamp
-              FROM blocks
-              WHERE block_timestamp <= $1::numeric(20, 0)
-              ORDER BY block_timestamp DESC
-              LIMIT 1",
-            &[block_timestamp.0.to_string()],
-        )
-        .await?
-        .first()
-        {
-            Some(block) => Ok(Block::try_from(block)?),
-            None => get_first_block(pool_explorer).await,
+    let finality = block_params.finality.unwrap_or_default();
+    let final_block_height = match finality {
+        types::query_params::Finality::Final => {
+            Some(rpc_helpers::get_final_block_height(rpc_client).await?)
         }
+        types::query_params::Finality::Optimistic => None,
+    };
+
+    let block = if let Some(block_height) = block_params.block_height {
+        get_block_by_height(pool_explorer, block_height.0).await?
+    } else if let Some(block_hash) = &block_params.block_hash {
+        get_block_by_hash(pool_explorer, block_hash).await?
+    } else if let Some(block_timestamp) = block_params
+        .block_timestamp_nanos
+        .map(|block_timestamp| block_timestamp.0)
+        .or(at_timestamp)
+    {
+        get_block_by_timestamp(pool_explorer, block_timestamp, final_block_height).await?
+    } else if let Some(final_block_height) = final_block_height {
+        get_block_by_timestamp(pool_explorer, u64::MAX, Some(final_block_height)).await?
     } else {
-        get_last_block(pool_explorer).await
+        get_last_block(pool_explorer).await?
+    };
+    if final_block_height.map_or(false, |final_block_height| {
+        block.height > final_block_height
+    }) {
+        return Err(errors::ErrorKind::InvalidInput(format!(
+            "block_height {} is not final yet",
+            block.height
+        ))
+        .into());
+    }
+    Ok(Block { finality, ..block })
+}
+
+async fn get_block_by_height(
+    ExplorerPool(pool_explorer): &ExplorerPool,
+    block_height: u64,
+) -> crate::Result<Block> {
+    match select_retry_or_panic::<BlockView>(
+        pool_explorer,
+        r"SELECT block_height, block_timestamp
+          FROM blocks
+          WHERE block_height = $1::numeric(20, 0)",
+        &[block_height.to_string()],
+    )
+    .await?
+    .first()
+    {
+        None => Err(errors::ErrorKind::InvalidInput(format!(
+            "block_height {} is not found",
+            block_height
+        ))
+        .into()),
+        Some(block) => Ok(Block::try_from(block)?),
+    }
+}
+
+async fn get_block_by_hash(
+    ExplorerPool(pool_explorer): &ExplorerPool,
+    block_hash: &str,
+) -> crate::Result<Block> {
+    match select_retry_or_panic::<BlockView>(
+        pool_explorer,
+        r"SELECT block_height, block_timestamp
+          FROM blocks
+          WHERE block_hash = $1",
+        &[block_hash.to_string()],
+    )
+    .await?
+    .first()
+    {
+        None => Err(errors::ErrorKind::InvalidInput(format!(
+            "block_hash {} is not found",
+            block_hash
+        ))
+        .into()),
+        Some(block) => Ok(Block::try_from(block)?),
+    }
+}
+
+/// Returns the latest block before the given timestamp, or the first block if there's no such block
+async fn get_block_by_timestamp(
+    pool_explorer: &ExplorerPool,
+    block_timestamp: u64,
+    max_block_height: Option<u64>,
+) -> crate::Result<Block> {
+    match select_retry_or_panic::<BlockView>(
+        &pool_explorer.0,
+        r"SELECT block_height, block_timestamp
+          FROM blocks
+          WHERE block_timestamp <= $1::numeric(20, 0)
+              AND block_height <= $2::numeric(20, 0)
+          ORDER BY block_timestamp DESC
+          LIMIT 1",
+        &[
+            block_timestamp.to_string(),
+            max_block_height.unwrap_or(u64::MAX).to_string(),
+        ],
+    )
+    .await?
+    .first()
+    {
+        Some(block) => Ok(Block::try_from(block)?),
+        None => get_first_block(pool_explorer).await,
     }
 }
 
@@ -151,7 +261,11 @@ pub(crate) async fn get_last_block(
     .first()
     {
         None => Err(errors::ErrorKind::DBError("blocks table is empty".to_string()).into()),
-        Some(block) => Ok(Block::try_from(block)?),
+        Some(block) => {
+            let block = Block::try_from(block)?;
+            cache::update_latest_block_height(block.height);
+            Ok(block)
+        }
     }
 }
 
@@ -176,6 +290,79 @@ pub(crate) async fn get_previous_block(
     }
 }
 
+/// Finds the transactions which produced the given receipts, the result has the same order as `receipt_ids`.
+/// Gives the empty items if `with_transaction_info` is not set.
+/// The transaction is None if the receipt is not found in the DB
+pub(crate) async fn get_receipt_transactions(
+    pool_explorer: &ExplorerPool,
+    receipt_ids: Vec<Option<String>>,
+    with_transaction_info: bool,
+) -> crate::Result<Vec<ReceiptTransaction>> {
+    if !with_transaction_info {
+        return Ok(receipt_ids
+            .iter()
+            .map(|_| ReceiptTransaction::default())
+            .collect());
+    }
+    let transactions = get_transactions_by_receipt_ids(
+        pool_explorer,
+        &receipt_ids
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<String>>(),
+    )
+    .await?;
+
+    let mut result = vec![];
+    for receipt_id in receipt_ids {
+        let transaction = receipt_id.as_ref().and_then(|id| transactions.get(id));
+        result.push(ReceiptTransaction {
+            transaction_hash: transaction.map(|transaction| transaction.transaction_hash.clone()),
+            signer_account_id: match transaction {
+                Some(transaction) => {
+                    Some(types::AccountId::from_str(&transaction.signer_account_id)?)
+                }
+                None => None,
+            },
+            receipt_id,
+        });
+    }
+    Ok(result)
+}
+
+async fn get_transactions_by_receipt_ids(
+    ExplorerPool(pool_explorer): &ExplorerPool,
+    receipt_ids: &[String],
+) -> crate::Result<std::collections::HashMap<String, TransactionInfo>> {
+    if receipt_ids.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+    let transactions = select_retry_or_panic::<TransactionInfo>(
+        pool_explorer,
+        r"SELECT
+              receipts.receipt_id,
+              transactions.transaction_hash,
+              transactions.signer_account_id
+          FROM receipts
+              JOIN transactions ON receipts.originated_from_transaction_hash = transactions.transaction_hash
+          WHERE receipts.receipt_id = ANY($1::text[])",
+        &[to_sql_array(receipt_ids)],
+    )
+    .await?;
+
+    Ok(transactions
+        .into_iter()
+        .map(|transaction| (transaction.receipt_id.clone(), transaction))
+        .collect())
+}
+
+/// Builds Postgres array literal, so that the list could be passed as the one substitution item.
+/// Should be used only for the values without commas and braces (hashes, numbers)
+pub(crate) fn to_sql_array(items: &[String]) -> String {
+    format!("{{{}}}", items.join(","))
+}
+
 pub(crate) async fn select_retry_or_panic<T: Send + Unpin + for<'r> sqlx::FromRow<'r, PgRow>>(
     pool: &sqlx::Pool<sqlx::Postgres>,
     query: &str,
@@ -191,11 +378,12 @@ pub(crate) async fn select_retry_or_panic<T: Send + Unpin + for<'r> sqlx::FromRo
         substitution_items.join(", ")
     );
 
+    let retry_count = DB_RETRY_COUNT.load(Ordering::Relaxed);
     loop {
-        if retry_attempt == DB_RETRY_COUNT {
+        if retry_attempt == retry_count {
             return Err(errors::ErrorKind::DBError(format!(
                 "Failed to perform query to database after {} attempts. Stop trying.",
-                DB_RETRY_COUNT
+                retry_count
             )));
         }
         retry_attempt += 1;
@@ -219,6 +407,7 @@ pub(crate) async fn select_retry_or_panic<T: Send + Unpin + for<'r> sqlx::FromRo
                     substitution_items.join(", "),
                     interval.as_millis(),
                 );
+                deadline::check_retry(interval)?;
                 tokio::time::sleep(interval).await;
                 if interval < MAX_DELAY_TIME {
                     interval *= 2;
@@ -227,3 +416,109 @@ pub(crate) async fn select_retry_or_panic<T: Send + Unpin + for<'r> sqlx::FromRo
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+
+    fn get_block_params() -> types::query_params::BlockParams {
+        types::query_params::BlockParams {
+            block_timestamp_nanos: None,
+            block_height: None,
+            block_hash: None,
+            at: None,
+            finality: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checked_get_block_by_hash() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let expected = get_block();
+        let block_hash: (String,) =
+            sqlx::query_as("SELECT block_hash FROM blocks WHERE block_height = $1::numeric(20, 0)")
+                .bind(expected.height.to_string())
+                .fetch_one(&pool_explorer.0)
+                .await
+                .unwrap();
+
+        let block = checked_get_block(
+            &pool_explorer,
+            &rpc_client,
+            &types::query_params::BlockParams {
+                block_hash: Some(block_hash.0),
+                ..get_block_params()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(block.height, expected.height);
+        assert_eq!(block.timestamp, expected.timestamp);
+
+        let error = checked_get_block(
+            &pool_explorer,
+            &rpc_client,
+            &types::query_params::BlockParams {
+                block_hash: Some("unknown".to_string()),
+                ..get_block_params()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code, 400);
+    }
+
+    #[tokio::test]
+    async fn test_checked_get_block_at() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let expected = get_block();
+
+        let block = checked_get_block(
+            &pool_explorer,
+            &rpc_client,
+            &types::query_params::BlockParams {
+                at: Some("2022-12-12T17:54:52.546051383Z".to_string()),
+                ..get_block_params()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(block.height, expected.height);
+
+        // The unix timestamp is ambiguous for the user input, only ISO 8601 is accepted
+        let error = checked_get_block(
+            &pool_explorer,
+            &rpc_client,
+            &types::query_params::BlockParams {
+                at: Some("1670867692546".to_string()),
+                ..get_block_params()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code, 400);
+    }
+
+    #[tokio::test]
+    async fn test_checked_get_block_multiple_selectors() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+
+        let error = checked_get_block(
+            &pool_explorer,
+            &rpc_client,
+            &types::query_params::BlockParams {
+                block_height: Some(types::U64::from(get_block().height)),
+                at: Some("2022-12-12T17:54:52Z".to_string()),
+                ..get_block_params()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code, 400);
+        assert!(error.message.contains("block_height, at found"));
+    }
+}
@@ -0,0 +1,168 @@
+use actix_web::{body, dev, ResponseError};
+
+use crate::{config, errors};
+
+// The client may ask for the shorter or longer (up to `deadline.max_timeout_ms`) deadline
+const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout-ms";
+
+tokio::task_local! {
+    static DEADLINE: Deadline;
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Deadline {
+    at: std::time::Instant,
+    timeout: std::time::Duration,
+}
+
+/// Gives the time budget of the request: the default one or the one from `x-request-timeout-ms` header
+pub(crate) fn get_timeout(
+    req: &dev::ServiceRequest,
+    deadline_config: &config::DeadlineConfig,
+) -> Result<std::time::Duration, errors::ErrorKind> {
+    let timeout_ms = match req.headers().get(REQUEST_TIMEOUT_HEADER) {
+        None => deadline_config.timeout_ms,
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|timeout_ms| (1..=deadline_config.max_timeout_ms).contains(timeout_ms))
+            .ok_or_else(|| {
+                errors::ErrorKind::InvalidInput(format!(
+                    "{} header should be a number of milliseconds in range [1, {}]",
+                    REQUEST_TIMEOUT_HEADER, deadline_config.max_timeout_ms
+                ))
+            })?,
+    };
+    Ok(std::time::Duration::from_millis(timeout_ms))
+}
+
+/// Runs the request with the deadline available to DB and RPC helpers.
+/// Answers with the retriable timeout error if the request does not finish in time
+pub(crate) async fn with_deadline<B, F>(
+    request: actix_web::HttpRequest,
+    timeout: Result<std::time::Duration, errors::ErrorKind>,
+    response: F,
+) -> Result<dev::ServiceResponse<body::BoxBody>, actix_web::Error>
+where
+    B: body::MessageBody + 'static,
+    F: std::future::Future<Output = Result<dev::ServiceResponse<B>, actix_web::Error>>,
+{
+    let timeout = match timeout {
+        Ok(timeout) => timeout,
+        Err(err) => return Ok(get_error_response(request, err)),
+    };
+    let deadline = Deadline {
+        at: std::time::Instant::now() + timeout,
+        timeout,
+    };
+    match tokio::time::timeout(timeout, DEADLINE.scope(deadline, response)).await {
+        Ok(response) => Ok(response?.map_into_boxed_body()),
+        Err(_) => Ok(get_error_response(request, get_timeout_error(timeout))),
+    }
+}
+
+/// Checks that we still have time to wait `interval` and retry.
+/// There is no deadline outside of the requests (e.g. in the background tasks)
+pub(crate) fn check_retry(interval: std::time::Duration) -> Result<(), errors::ErrorKind> {
+    match DEADLINE.try_with(|deadline| *deadline) {
+        Ok(deadline) if std::time::Instant::now() + interval >= deadline.at => {
+            Err(get_timeout_error(deadline.timeout))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn get_timeout_error(timeout: std::time::Duration) -> errors::ErrorKind {
+    errors::ErrorKind::Timeout(format!(
+        "The request did not finish in {} ms",
+        timeout.as_millis()
+    ))
+}
+
+fn get_error_response(
+    request: actix_web::HttpRequest,
+    err: errors::ErrorKind,
+) -> dev::ServiceResponse<body::BoxBody> {
+    dev::ServiceResponse::new(request, errors::Error::from(err).error_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::dev::Service;
+    use actix_web::http::StatusCode;
+    use actix_web::{test, web, App, HttpResponse};
+
+    async fn get_slow() -> HttpResponse {
+        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        HttpResponse::Ok().finish()
+    }
+
+    async fn get_retried() -> HttpResponse {
+        match check_retry(std::time::Duration::from_secs(10)) {
+            Ok(()) => HttpResponse::Ok().finish(),
+            Err(err) => errors::Error::from(err).error_response(),
+        }
+    }
+
+    #[test]
+    fn test_check_retry_without_deadline() {
+        assert!(check_retry(std::time::Duration::from_secs(1000)).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_deadline() {
+        let deadline_config = config::DeadlineConfig {
+            timeout_ms: 100,
+            max_timeout_ms: 20_000,
+        };
+        let app = test::init_service(
+            App::new()
+                .wrap_fn(move |req, srv| {
+                    let request = req.request().clone();
+                    let timeout = get_timeout(&req, &deadline_config);
+                    with_deadline(request, timeout, srv.call(req))
+                })
+                .route("/slow", web::get().to(get_slow))
+                .route("/retried", web::get().to(get_retried)),
+        )
+        .await;
+
+        let res =
+            test::call_service(&app, test::TestRequest::get().uri("/slow").to_request()).await;
+        assert_eq!(res.status(), StatusCode::GATEWAY_TIMEOUT);
+        let error: errors::Error = test::read_body_json(res).await;
+        assert_eq!(error.code, 504);
+        assert!(error.retriable);
+
+        let res =
+            test::call_service(&app, test::TestRequest::get().uri("/retried").to_request()).await;
+        assert_eq!(res.status(), StatusCode::GATEWAY_TIMEOUT);
+        let error: errors::Error = test::read_body_json(res).await;
+        assert_eq!(error.code, 504);
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/retried")
+                .insert_header((REQUEST_TIMEOUT_HEADER, "15000"))
+                .to_request(),
+        )
+        .await;
+        assert!(res.status().is_success());
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/retried")
+                .insert_header((REQUEST_TIMEOUT_HEADER, "30000"))
+                .to_request(),
+        )
+        .await;
+        // Only the timeout has its own HTTP status
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let error: errors::Error = test::read_body_json(res).await;
+        assert_eq!(error.code, 400);
+    }
+}
@@ -1,6 +1,9 @@
+use std::str::FromStr;
+
 use paperclip::actix::{api_v2_errors, Apiv2Schema};
 
 use near_jsonrpc_client::errors::JsonRpcError;
+use near_jsonrpc_primitives::types::blocks::RpcBlockError;
 use near_jsonrpc_primitives::types::query::RpcQueryError;
 
 #[derive(Debug, strum::EnumIter)]
@@ -10,13 +13,17 @@ pub enum ErrorKind {
     InternalError(String),
     ContractError(String),
     RPCError(String),
+    Timeout(String),
 }
 
 /// Instead of utilizing HTTP status codes to describe node errors (which often
 /// do not have a good analog), rich errors are returned using this object.
+/// The only exception is the timeout, which is given with 504 status.
 #[api_v2_errors(
     code = 500,
-    description = "See the inner `code` value to get more details"
+    description = "See the inner `code` value to get more details",
+    code = 504,
+    description = "The request did not finish in time, see `x-request-timeout-ms` header"
 )]
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
 pub struct Error {
@@ -67,6 +74,11 @@ impl Error {
                 message: format!("RPC error: {}", message),
                 retriable: true,
             },
+            ErrorKind::Timeout(message) => Self {
+                code: 504,
+                message: format!("Timeout: {}", message),
+                retriable: true,
+            },
         }
     }
 }
@@ -81,9 +93,18 @@ where
 }
 
 impl actix_web::ResponseError for Error {
+    // All the errors are 500 except the timeout, so that the proxies and clients could tell it
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        if self.code == 504 {
+            actix_web::http::StatusCode::GATEWAY_TIMEOUT
+        } else {
+            actix_web::http::StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
     fn error_response(&self) -> actix_web::HttpResponse {
         let data = paperclip::actix::web::Json(self);
-        actix_web::HttpResponse::InternalServerError().json(data)
+        actix_web::HttpResponse::build(self.status_code()).json(data)
     }
 }
 
@@ -93,6 +114,12 @@ impl From<JsonRpcError<RpcQueryError>> for ErrorKind {
     }
 }
 
+impl From<JsonRpcError<RpcBlockError>> for ErrorKind {
+    fn from(error: JsonRpcError<RpcBlockError>) -> Self {
+        Self::RPCError(format!("{:#?}", error))
+    }
+}
+
 impl From<serde_json::Error> for ErrorKind {
     fn from(error: serde_json::Error) -> Self {
         Self::InternalError(format!("Serialization failure: {:#?}", error))
@@ -111,3 +138,12 @@ pub(crate) fn validate_account_id(account_id: &str) -> Result<(), validator::Val
         Err(_) => Err(validator::ValidationError::new("")),
     }
 }
+
+pub(crate) fn validate_transaction_hash(
+    transaction_hash: &str,
+) -> Result<(), validator::ValidationError> {
+    match near_primitives::hash::CryptoHash::from_str(transaction_hash) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(validator::ValidationError::new("")),
+    }
+}
@@ -0,0 +1,129 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{config, db_helpers};
+
+const STATUS_OK: &str = "OK";
+const STATUS_FAILED: &str = "FAILED";
+
+#[derive(Debug, serde::Serialize)]
+struct HealthResponse {
+    status: &'static str,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ReadinessResponse {
+    status: &'static str,
+    explorer_db: DependencyStatus,
+    balances_db: DependencyStatus,
+    rpc: DependencyStatus,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DependencyStatus {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Liveness probe: the server is able to answer
+pub(crate) async fn live() -> HttpResponse {
+    HttpResponse::Ok().json(HealthResponse { status: STATUS_OK })
+}
+
+/// Readiness probe: all the dependencies answer within the configured time.
+/// Gives 503 with the status of each dependency otherwise
+pub(crate) async fn ready(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    pool_balances: web::Data<db_helpers::BalancesPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    health_config: web::Data<config::HealthConfig>,
+) -> HttpResponse {
+    let timeout = std::time::Duration::from_millis(health_config.timeout_ms);
+    let (explorer_db, balances_db, rpc) = futures::join!(
+        check_dependency(timeout, async {
+            sqlx::query("SELECT 1")
+                .execute(&pool_explorer.0)
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        }),
+        check_dependency(timeout, async {
+            sqlx::query("SELECT 1")
+                .execute(&pool_balances.0)
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        }),
+        check_dependency(timeout, async {
+            rpc_client
+                .call(
+                    &near_jsonrpc_client::methods::status::RpcStatusRequest,
+                    None,
+                )
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        }),
+    );
+
+    let is_ready = [&explorer_db, &balances_db, &rpc]
+        .iter()
+        .all(|dependency| dependency.error.is_none());
+    let response = ReadinessResponse {
+        status: if is_ready { STATUS_OK } else { STATUS_FAILED },
+        explorer_db,
+        balances_db,
+        rpc,
+    };
+    if is_ready {
+        HttpResponse::Ok().json(response)
+    } else {
+        HttpResponse::ServiceUnavailable().json(response)
+    }
+}
+
+async fn check_dependency<F>(timeout: std::time::Duration, check: F) -> DependencyStatus
+where
+    F: std::future::Future<Output = Result<(), String>>,
+{
+    let error = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(())) => None,
+        Ok(Err(err)) => Some(err),
+        Err(_) => Some(format!("No response in {} ms", timeout.as_millis())),
+    };
+    DependencyStatus {
+        status: if error.is_none() {
+            STATUS_OK
+        } else {
+            STATUS_FAILED
+        },
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_dependency() {
+        let timeout = std::time::Duration::from_millis(100);
+
+        let dependency = check_dependency(timeout, async { Ok(()) }).await;
+        assert_eq!(dependency.status, STATUS_OK);
+        assert!(dependency.error.is_none());
+
+        let dependency =
+            check_dependency(timeout, async { Err("connection refused".to_string()) }).await;
+        assert_eq!(dependency.status, STATUS_FAILED);
+        assert_eq!(dependency.error.as_deref(), Some("connection refused"));
+
+        let dependency = check_dependency(timeout, async {
+            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+            Ok(())
+        })
+        .await;
+        assert_eq!(dependency.status, STATUS_FAILED);
+        assert_eq!(dependency.error.as_deref(), Some("No response in 100 ms"));
+    }
+}
@@ -0,0 +1,252 @@
+use actix_web::http::{header, Method, StatusCode};
+use actix_web::{body, dev, HttpResponse};
+use sha2::Digest;
+
+use crate::{cache, config};
+
+// The query parameters which pin the response to the specific block
+const BLOCK_SELECTORS: &[&str] = &["block_height", "block_timestamp_nanos", "block_hash", "at"];
+
+/// The part of the request we need after the request is moved to the handler
+pub(crate) struct HttpCacheRequest {
+    is_get: bool,
+    is_block_pinned: bool,
+    if_none_match: Option<String>,
+}
+
+impl HttpCacheRequest {
+    pub(crate) fn new(req: &dev::ServiceRequest) -> Self {
+        let is_block_pinned = req
+            .query_string()
+            .split('&')
+            .filter_map(|pair| pair.split('=').next())
+            .any(|key| BLOCK_SELECTORS.contains(&key));
+        Self {
+            is_get: req.method() == Method::GET,
+            is_block_pinned,
+            if_none_match: req
+                .headers()
+                .get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+        }
+    }
+}
+
+/// Adds `Cache-Control` and `ETag` headers to the successful JSON responses containing `block_height`.
+/// Answers 304 Not Modified if the client already has the same response
+pub(crate) async fn set_cache_headers<B, F>(
+    request: HttpCacheRequest,
+    http_cache_config: config::HttpCacheConfig,
+    response_cache: &cache::ResponseCache,
+    response: F,
+) -> Result<dev::ServiceResponse<body::BoxBody>, actix_web::Error>
+where
+    B: body::MessageBody + 'static,
+    F: std::future::Future<Output = Result<dev::ServiceResponse<B>, actix_web::Error>>,
+{
+    let response = response.await?;
+    if !request.is_get || response.status() != StatusCode::OK || !is_json(response.headers()) {
+        return Ok(response.map_into_boxed_body());
+    }
+
+    let (req, res) = response.into_parts();
+    let (res, res_body) = res.into_parts();
+    let bytes = body::to_bytes(res_body).await.map_err(|err| {
+        let err: Box<dyn std::error::Error> = err.into();
+        actix_web::error::ErrorInternalServerError(err.to_string())
+    })?;
+    let block_height = match get_block_height(&bytes) {
+        Some(block_height) => block_height,
+        None => {
+            return Ok(dev::ServiceResponse::new(
+                req,
+                res.set_body(bytes).map_into_boxed_body(),
+            ))
+        }
+    };
+
+    let etag = get_etag(&bytes);
+    let cache_control = get_cache_control(
+        request.is_block_pinned && response_cache.is_cacheable(block_height),
+        &http_cache_config,
+    );
+    let mut res = if request
+        .if_none_match
+        .as_deref()
+        .map_or(false, |if_none_match| is_etag_matched(if_none_match, &etag))
+    {
+        HttpResponse::NotModified().finish()
+    } else {
+        res.set_body(bytes).map_into_boxed_body()
+    };
+    let headers = res.headers_mut();
+    headers.insert(
+        header::ETAG,
+        header::HeaderValue::from_str(&etag).map_err(actix_web::error::ErrorInternalServerError)?,
+    );
+    headers.insert(
+        header::CACHE_CONTROL,
+        header::HeaderValue::from_str(&cache_control)
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+    );
+    Ok(dev::ServiceResponse::new(req, res))
+}
+
+fn is_json(headers: &header::HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value.starts_with("application/json"))
+}
+
+// All our responses have `block_height` on the top level, we serialize it as a string
+fn get_block_height(bytes: &[u8]) -> Option<u64> {
+    match serde_json::from_slice::<serde_json::Value>(bytes)
+        .ok()?
+        .get("block_height")?
+    {
+        serde_json::Value::String(value) => value.parse().ok(),
+        serde_json::Value::Number(value) => value.as_u64(),
+        _ => None,
+    }
+}
+
+// The data providers could return different data for the same block (e.g. the latest block
+// is not final yet), so the ETag is computed from the response itself
+fn get_etag(bytes: &[u8]) -> String {
+    let digest = sha2::Sha256::digest(bytes);
+    format!("\"{}\"", hex::encode(&digest[..16]))
+}
+
+fn get_cache_control(is_immutable: bool, http_cache_config: &config::HttpCacheConfig) -> String {
+    if is_immutable {
+        format!(
+            "public, max-age={}, immutable",
+            http_cache_config.pinned_max_age_seconds
+        )
+    } else {
+        format!(
+            "public, max-age={}",
+            http_cache_config.latest_max_age_seconds
+        )
+    }
+}
+
+// https://httpwg.org/specs/rfc9110.html#field.if-none-match, weak comparison
+fn is_etag_matched(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|value| value == "*" || value.strip_prefix("W/").unwrap_or(value) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::dev::Service;
+    use actix_web::{test, web, App};
+
+    async fn get_balance() -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({ "balance": "1", "block_height": "100" }))
+    }
+
+    async fn get_error() -> HttpResponse {
+        HttpResponse::BadRequest().json(serde_json::json!({ "block_height": "100" }))
+    }
+
+    #[test]
+    fn test_etag_matched() {
+        let etag = get_etag(br#"{"balance": "1", "block_height": "100"}"#);
+        assert_eq!(
+            etag,
+            get_etag(br#"{"balance": "1", "block_height": "100"}"#)
+        );
+        assert_ne!(
+            etag,
+            get_etag(br#"{"balance": "2", "block_height": "100"}"#)
+        );
+        assert_ne!(
+            etag,
+            get_etag(br#"{"balance": "1", "block_height": "101"}"#)
+        );
+        assert!(is_etag_matched(&etag, &etag));
+        assert!(is_etag_matched(&format!("\"abc\", W/{}", etag), &etag));
+        assert!(is_etag_matched("*", &etag));
+        assert!(!is_etag_matched("\"abc\"", &etag));
+    }
+
+    #[test]
+    fn test_block_height() {
+        assert_eq!(get_block_height(br#"{"block_height": "100"}"#), Some(100));
+        assert_eq!(get_block_height(br#"{"block_height": 100}"#), Some(100));
+        assert_eq!(get_block_height(br#"{"balance": "1"}"#), None);
+        assert_eq!(get_block_height(b"not a json"), None);
+    }
+
+    #[actix_web::test]
+    async fn test_cache_headers() {
+        let response_cache =
+            std::rc::Rc::new(cache::ResponseCache::new(&config::CacheConfig::default()));
+        response_cache.update_latest_block_height(1000);
+        let app = test::init_service(
+            App::new()
+                .wrap_fn(move |req, srv| {
+                    let request = HttpCacheRequest::new(&req);
+                    let response_cache = response_cache.clone();
+                    let response = srv.call(req);
+                    async move {
+                        set_cache_headers(
+                            request,
+                            config::HttpCacheConfig::default(),
+                            &response_cache,
+                            response,
+                        )
+                        .await
+                    }
+                })
+                .route("/balance", web::get().to(get_balance))
+                .route("/error", web::get().to(get_error)),
+        )
+        .await;
+
+        let res =
+            test::call_service(&app, test::TestRequest::get().uri("/balance").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=1"
+        );
+        let etag = res.headers().get(header::ETAG).unwrap().clone();
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/balance")
+                .insert_header((header::IF_NONE_MATCH, etag.clone()))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), &etag);
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/balance?block_height=100")
+                .to_request(),
+        )
+        .await;
+        assert_eq!(
+            res.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=31536000, immutable"
+        );
+        // The same body gives the same ETag regardless of the URL
+        assert_eq!(res.headers().get(header::ETAG).unwrap(), &etag);
+
+        let res =
+            test::call_service(&app, test::TestRequest::get().uri("/error").to_request()).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert!(res.headers().get(header::ETAG).is_none());
+    }
+}
@@ -1,21 +1,25 @@
 use actix_cors::Cors;
+use actix_web::dev::Service;
 use actix_web::{App, HttpServer, ResponseError};
 use actix_web_prom::PrometheusMetricsBuilder;
 use actix_web_validator::PathConfig;
 use paperclip::actix::{web, OpenApiExt};
 pub(crate) use sqlx::types::BigDecimal;
 
+mod cache;
 mod config;
 mod db_helpers;
+mod deadline;
 mod errors;
+mod health;
+mod http_cache;
 mod modules;
 mod rpc_helpers;
+mod rpc_pool;
 mod types;
 
 pub(crate) const LOGGER_MSG: &str = "near_enhanced_api";
 
-pub(crate) const MIN_EVENT_INDEX: u128 = (10_u128).pow(34);
-
 pub(crate) type Result<T> = std::result::Result<T, errors::Error>;
 
 fn get_cors(cors_allowed_origins: &[String]) -> Cors {
@@ -35,16 +39,10 @@ fn get_cors(cors_allowed_origins: &[String]) -> Cors {
         .max_age(3600)
 }
 
-fn get_api_base_path() -> String {
-    std::env::var("API_BASE_PATH").unwrap_or_else(|_| "".to_string())
-}
-
-fn get_api_spec_base_path() -> String {
-    std::env::var("API_SPEC_BASE_PATH").unwrap_or_else(|_| "".to_string())
-}
-
-async fn playground_ui() -> impl actix_web::Responder {
-    let spec_base_path = get_api_spec_base_path();
+async fn playground_ui(
+    api_config: actix_web::web::Data<config::ApiConfig>,
+) -> impl actix_web::Responder {
+    let spec_base_path = &api_config.spec_base_path;
     actix_web::HttpResponse::Ok()
         .insert_header(actix_web::http::header::ContentType::html())
         .body(
@@ -71,6 +69,12 @@ async fn playground_ui() -> impl actix_web::Responder {
         )
 }
 
+// The startup failures are logged and returned from `main`, the same way as the config errors
+fn startup_error(context: &str, err: impl std::fmt::Display) -> std::io::Error {
+    tracing::error!(target: crate::LOGGER_MSG, "{}: {}", context, err);
+    std::io::Error::new(std::io::ErrorKind::Other, format!("{}: {}", context, err))
+}
+
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
@@ -95,38 +99,67 @@ async fn main() -> std::io::Result<()> {
         .build()
         .unwrap();
 
-    // See https://docs.rs/sqlx/latest/sqlx/struct.Pool.html#2-connection-limits-mysql-mssql-postgres
-    // for setting connection limits.
-    let db_max_connections: u32 = std::env::var("DATABASE_MAX_CONNECTIONS")
-        .unwrap_or_else(|_| "97".to_string())
-        .parse()
-        .expect("Failed to parse DATABASE_MAX_CONNECTIONS value as u32");
+    let config::Config {
+        addr,
+        cors_allowed_origins,
+        database,
+        rpc,
+        api,
+        limits,
+        nft_reference,
+        nft_gateways,
+        cache: cache_config,
+        http_cache: http_cache_config,
+        status: status_config,
+        health: health_config,
+        deadline: deadline_config,
+    } = config::Config::load().map_err(|err| {
+        tracing::error!(target: crate::LOGGER_MSG, "{}", err);
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string())
+    })?;
+    db_helpers::init(&database);
+    rpc_helpers::init(&rpc);
+    types::query_params::init(&limits);
 
-    let explorer_db_url = &std::env::var("EXPLORER_DATABASE_URL")
-        .expect("failed to get database url from EXPLORER_DATABASE_URL env variable");
     let pool_explorer = sqlx::postgres::PgPoolOptions::new()
-        .max_connections(db_max_connections)
-        .connect(explorer_db_url)
+        .max_connections(database.max_connections)
+        .acquire_timeout(std::time::Duration::from_millis(
+            database.acquire_timeout_ms,
+        ))
+        .connect(&database.explorer_url)
         .await
-        .expect("failed to connect to the database");
+        .map_err(|err| startup_error("failed to connect to the database", err))?;
 
-    let balances_db_url = &std::env::var("BALANCES_DATABASE_URL")
-        .expect("failed to get database url from BALANCES_DATABASE_URL env variable");
     let pool_balances = sqlx::postgres::PgPoolOptions::new()
-        .max_connections(db_max_connections)
-        .connect(balances_db_url)
+        .max_connections(database.max_connections)
+        .acquire_timeout(std::time::Duration::from_millis(
+            database.acquire_timeout_ms,
+        ))
+        .connect(&database.balances_url)
         .await
-        .expect("failed to connect to the balances database");
+        .map_err(|err| startup_error("failed to connect to the balances database", err))?;
 
-    let rpc_url =
-        &std::env::var("RPC_URL").expect("failed to get RPC url from RPC_URL env variable");
-    let rpc_client = near_jsonrpc_client::JsonRpcClient::connect(rpc_url);
+    cache::init(&cache_config, &prometheus.registry)
+        .map_err(|err| startup_error("failed to register the cache metrics", err))?;
+    rpc_pool::init(&prometheus.registry)
+        .map_err(|err| startup_error("failed to register the RPC metrics", err))?;
 
-    let config::Config {
-        addr,
-        cors_allowed_origins,
-        limits,
-    } = config::Config::default();
+    // Shared between the workers, so that the endpoints health is common
+    let rpc_client = web::Data::new(rpc_pool::RpcPool::new(&rpc));
+
+    // Shared between the workers, so that the cache is common
+    let reference_resolver = web::Data::new(modules::nft::ReferenceResolver::new(
+        std::sync::Arc::new(modules::nft::HttpReferenceFetcher::new()),
+        nft_reference,
+        nft_gateways.clone(),
+    ));
+
+    tokio::spawn(modules::status::watch_indexer_lag(
+        db_helpers::ExplorerPool(pool_explorer.clone()),
+        db_helpers::BalancesPool(pool_balances.clone()),
+        rpc_client.clone().into_inner(),
+        status_config.clone(),
+    ));
 
     let server = HttpServer::new(move || {
         let json_config = web::JsonConfig::default()
@@ -152,13 +185,13 @@ async fn main() -> std::io::Result<()> {
         });
 
         let mut spec = paperclip::v2::models::DefaultApiRaw::default();
-        if let Ok(api_server_public_host) = std::env::var("API_SERVER_PUBLIC_HOST") {
+        if let Some(api_server_public_host) = &api.public_host {
             spec.schemes
                 .insert(paperclip::v2::models::OperationProtocol::Https);
-            spec.host = Some(api_server_public_host);
+            spec.host = Some(api_server_public_host.clone());
         }
-        let base_path = get_api_base_path();
-        let spec_base_path = get_api_spec_base_path();
+        let base_path = api.base_path.clone();
+        let spec_base_path = api.spec_base_path.clone();
         spec.base_path = Some(base_path.clone());
         spec.info = paperclip::v2::models::Info {
             version: "0.1".into(),
@@ -179,18 +212,50 @@ We would love to hear from you on the data APIs you need, please leave feedback
         let mut app = App::new()
             .app_data(json_config)
             .app_data(path_config)
+            .wrap_fn({
+                let deadline_config = deadline_config.clone();
+                move |req, srv| {
+                    let request = req.request().clone();
+                    let timeout = deadline::get_timeout(&req, &deadline_config);
+                    deadline::with_deadline(request, timeout, srv.call(req))
+                }
+            })
+            .wrap_fn({
+                let http_cache_config = http_cache_config.clone();
+                move |req, srv| {
+                    let request = http_cache::HttpCacheRequest::new(&req);
+                    http_cache::set_cache_headers(
+                        request,
+                        http_cache_config.clone(),
+                        cache::get_global(),
+                        srv.call(req),
+                    )
+                }
+            })
+            .wrap_fn(|req, srv| modules::status::with_indexer_lag_header(srv.call(req)))
             .wrap(actix_web::middleware::Logger::default())
             .wrap(prometheus.clone())
             .app_data(web::Data::new(db_helpers::ExplorerPool(pool_explorer.clone())))
             .app_data(web::Data::new(db_helpers::BalancesPool(pool_balances.clone())))
-            .app_data(web::Data::new(rpc_client.clone()))
+            .app_data(rpc_client.clone())
+            .app_data(reference_resolver.clone())
+            .app_data(web::Data::new(nft_gateways.clone()))
+            .app_data(web::Data::new(status_config.clone()))
+            .app_data(web::Data::new(health_config.clone()))
+            .app_data(web::Data::new(api.clone()))
             .wrap(get_cors(&cors_allowed_origins))
             .route("/", actix_web::web::get().to(playground_ui))
+            .route("/health/live", actix_web::web::get().to(health::live))
+            .route("/health/ready", actix_web::web::get().to(health::ready))
             .wrap_api_with_spec(spec);
 
+        app = app.configure(modules::accounts::register_services);
+        app = app.configure(modules::contracts::register_services);
         app = app.configure(modules::native::register_services);
         app = app.configure(modules::ft::register_services);
         app = app.configure(modules::nft::register_services);
+        app = app.configure(modules::status::register_services);
+        app = app.configure(modules::transactions::register_services);
 
         app.with_json_spec_at(format!("{spec_base_path}/v2.json").as_str())
             .with_json_spec_v3_at(format!("{spec_base_path}/v3.json").as_str())
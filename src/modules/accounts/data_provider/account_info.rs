@@ -0,0 +1,163 @@
+use std::str::FromStr;
+
+use crate::modules::accounts;
+use crate::{db_helpers, errors, modules, types};
+
+pub(crate) async fn get_account(
+    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    rpc_client: &crate::rpc_pool::RpcPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+) -> crate::Result<accounts::schemas::Account> {
+    // The account could be deleted and created again, we need the one existed at the given block.
+    // Genesis accounts do not have the creation receipt
+    let account_info = db_helpers::select_retry_or_panic::<super::models::AccountInfo>(
+        pool_explorer,
+        r"
+            SELECT
+                accounts.created_by_receipt_id,
+                accounts.deleted_by_receipt_id
+            FROM accounts
+                LEFT JOIN receipts ON accounts.created_by_receipt_id = receipts.receipt_id
+            WHERE accounts.account_id = $1
+                AND COALESCE(receipts.included_in_block_timestamp, 0) <= $2::numeric(20, 0)
+            ORDER BY COALESCE(receipts.included_in_block_timestamp, 0) DESC
+            LIMIT 1
+        ",
+        &[account_id.to_string(), block.timestamp.to_string()],
+    )
+    .await?;
+    // The indexer could be a bit behind RPC, so it's fine if we don't have the account yet,
+    // RPC will tell us if the account does not exist
+    let (created_by_receipt_id, deleted_by_receipt_id) = match account_info.first() {
+        Some(info) => (
+            info.created_by_receipt_id.clone(),
+            info.deleted_by_receipt_id.clone(),
+        ),
+        None => (None, None),
+    };
+
+    let receipt_ids: Vec<String> = created_by_receipt_id
+        .iter()
+        .chain(deleted_by_receipt_id.iter())
+        .cloned()
+        .collect();
+    let mut changes = get_account_changes(pool_explorer, &receipt_ids).await?;
+    let created_by = created_by_receipt_id.and_then(|id| changes.remove(&id));
+    let deleted_by = deleted_by_receipt_id.and_then(|id| changes.remove(&id));
+
+    // RPC does not know anything about the deleted accounts
+    let is_deleted = deleted_by.as_ref().map_or(false, |deleted_by| {
+        deleted_by.block_timestamp_nanos.0 <= block.timestamp
+    });
+    let account_view = if is_deleted {
+        None
+    } else {
+        Some(modules::get_account_view(rpc_client, account_id, block.height).await?)
+    };
+
+    Ok(accounts::schemas::Account {
+        account_id: account_id.clone().into(),
+        amount: account_view.as_ref().map(|view| view.amount.into()),
+        locked: account_view.as_ref().map(|view| view.locked.into()),
+        code_hash: account_view.as_ref().map(|view| view.code_hash.to_string()),
+        storage_usage: account_view.as_ref().map(|view| view.storage_usage.into()),
+        created_by,
+        deleted_by,
+    })
+}
+
+async fn get_account_changes(
+    pool_explorer: &sqlx::Pool<sqlx::Postgres>,
+    receipt_ids: &[String],
+) -> crate::Result<std::collections::HashMap<String, accounts::schemas::AccountChange>> {
+    let mut result = std::collections::HashMap::new();
+    if receipt_ids.is_empty() {
+        return Ok(result);
+    }
+
+    let query = r"
+        SELECT
+            receipts.receipt_id,
+            receipts.predecessor_account_id,
+            receipts.originated_from_transaction_hash transaction_hash,
+            receipts.included_in_block_timestamp block_timestamp_nanos,
+            blocks.block_height
+        FROM receipts
+            JOIN blocks ON receipts.included_in_block_hash = blocks.block_hash
+        WHERE receipts.receipt_id = ANY($1::text[])
+    ";
+    let changes_info = db_helpers::select_retry_or_panic::<super::models::AccountChangeInfo>(
+        pool_explorer,
+        query,
+        &[db_helpers::to_sql_array(receipt_ids)],
+    )
+    .await?;
+    for info in changes_info {
+        result.insert(info.receipt_id.clone(), info.try_into()?);
+    }
+    Ok(result)
+}
+
+impl TryFrom<super::models::AccountChangeInfo> for accounts::schemas::AccountChange {
+    type Error = errors::Error;
+
+    fn try_from(info: super::models::AccountChangeInfo) -> crate::Result<Self> {
+        Ok(Self {
+            predecessor_account_id: types::AccountId::from_str(&info.predecessor_account_id)?,
+            receipt_id: info.receipt_id,
+            transaction_hash: info.transaction_hash,
+            block_timestamp_nanos: types::numeric::to_u64(&info.block_timestamp_nanos)?.into(),
+            block_height: types::numeric::to_u64(&info.block_height)?.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+
+    #[tokio::test]
+    async fn test_account() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+
+        let account_info = get_account(&pool_explorer, &rpc_client, &account, &block)
+            .await
+            .unwrap();
+        assert_eq!(account_info.account_id.0, account);
+        assert!(account_info.storage_usage.unwrap().0 > 0);
+        assert!(account_info.created_by.unwrap().block_timestamp_nanos.0 <= block.timestamp);
+        assert!(account_info.deleted_by.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_account_deleted() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("tezeract.near").unwrap();
+
+        let account_info = get_account(&pool_explorer, &rpc_client, &account, &block)
+            .await
+            .unwrap();
+        assert!(account_info.amount.is_none());
+        assert!(account_info.storage_usage.is_none());
+        assert!(account_info.deleted_by.unwrap().block_timestamp_nanos.0 <= block.timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_account_never_existed() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let account =
+            near_primitives::types::AccountId::from_str("two-idiots-and-a-half.near").unwrap();
+
+        let account_info = get_account(&pool_explorer, &rpc_client, &account, &block).await;
+        assert!(account_info.is_err());
+    }
+}
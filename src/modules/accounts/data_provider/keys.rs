@@ -0,0 +1,335 @@
+use crate::modules::accounts;
+use crate::{cache, db_helpers, errors, rpc_helpers, types};
+
+pub(crate) async fn get_access_keys(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+) -> crate::Result<Vec<accounts::schemas::AccessKey>> {
+    let mut keys: Vec<accounts::schemas::AccessKey> =
+        rpc_helpers::view_access_key_list(rpc_client, block.height, account_id)
+            .await?
+            .into_iter()
+            .map(|key| accounts::schemas::AccessKey {
+                public_key: key.public_key.to_string(),
+                nonce: key.access_key.nonce.into(),
+                permission: key.access_key.permission.into(),
+            })
+            .collect();
+    // RPC does not guarantee any order
+    keys.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+    Ok(keys)
+}
+
+/// The history at the old blocks never changes, so it is cached (see `cache::cached`)
+pub(crate) async fn get_access_keys_history(
+    pool_explorer: &db_helpers::ExplorerPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    pagination: &types::query_params::Pagination,
+) -> crate::Result<Vec<accounts::schemas::AccessKeyHistoryItem>> {
+    cache::cached(
+        "get_access_keys_history",
+        format!("{}|{:?}", account_id, pagination),
+        block.height,
+        load_access_keys_history(pool_explorer, account_id, block, pagination),
+    )
+    .await
+}
+
+async fn load_access_keys_history(
+    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    pagination: &types::query_params::Pagination,
+) -> crate::Result<Vec<accounts::schemas::AccessKeyHistoryItem>> {
+    let after = if let Some(index) = pagination.after_event_index {
+        types::EventIndex::new(index).action_parts()
+    } else {
+        types::ActionIndexParts {
+            // +1 because we need to include given timestamp to result. Query has strict less operator
+            timestamp_nanos: block.timestamp + 1,
+            shard_id: 0,
+            index_in_chunk: 0,
+            index_in_action_receipt: 0,
+        }
+    };
+
+    let query = r"
+        SELECT
+            action_receipt_actions.receipt_id,
+            action_receipt_actions.action_kind::text cause,
+            action_receipt_actions.args,
+            CASE WHEN execution_outcomes.status IN ('SUCCESS_VALUE', 'SUCCESS_RECEIPT_ID') THEN 'SUCCESS'
+                WHEN execution_outcomes.status = 'FAILURE' THEN 'FAILURE'
+                ELSE 'UNKNOWN'
+            END status,
+            receipts.originated_from_transaction_hash transaction_hash,
+            chunks.shard_id,
+            receipts.index_in_chunk,
+            action_receipt_actions.index_in_action_receipt,
+            receipts.included_in_block_timestamp block_timestamp_nanos,
+            blocks.block_height
+        FROM action_receipt_actions
+            JOIN receipts ON action_receipt_actions.receipt_id = receipts.receipt_id
+            JOIN chunks ON receipts.included_in_chunk_hash = chunks.chunk_hash
+            JOIN blocks ON receipts.included_in_block_hash = blocks.block_hash
+            LEFT JOIN execution_outcomes ON action_receipt_actions.receipt_id = execution_outcomes.receipt_id
+        WHERE action_receipt_actions.receipt_receiver_account_id = $1
+            AND action_receipt_actions.action_kind IN ('ADD_KEY', 'DELETE_KEY')
+            AND (receipts.included_in_block_timestamp, chunks.shard_id, receipts.index_in_chunk, action_receipt_actions.index_in_action_receipt)
+                < ($2::numeric(20, 0), $3::numeric(20, 0), $4::integer, $5::integer)
+        ORDER BY receipts.included_in_block_timestamp DESC,
+            chunks.shard_id DESC,
+            receipts.index_in_chunk DESC,
+            action_receipt_actions.index_in_action_receipt DESC
+        LIMIT $6::numeric(20, 0)
+    ";
+    let history_info = db_helpers::select_retry_or_panic::<super::models::AccessKeyHistoryInfo>(
+        pool_explorer,
+        query,
+        &[
+            account_id.to_string(),
+            after.timestamp_nanos.to_string(),
+            after.shard_id.to_string(),
+            after.index_in_chunk.to_string(),
+            after.index_in_action_receipt.to_string(),
+            pagination.limit.to_string(),
+        ],
+    )
+    .await?;
+
+    let mut result: Vec<accounts::schemas::AccessKeyHistoryItem> = vec![];
+    for history in history_info {
+        result.push(history.try_into()?);
+    }
+    Ok(result)
+}
+
+impl TryFrom<super::models::AccessKeyHistoryInfo> for accounts::schemas::AccessKeyHistoryItem {
+    type Error = errors::Error;
+
+    fn try_from(history: super::models::AccessKeyHistoryInfo) -> crate::Result<Self> {
+        let block_timestamp_nanos = types::numeric::to_u64(&history.block_timestamp_nanos)?;
+        let event_index = types::EventIndex::from_action_parts(types::ActionIndexParts {
+            timestamp_nanos: block_timestamp_nanos,
+            shard_id: types::numeric::to_u64(&history.shard_id)? as u32,
+            index_in_chunk: history.index_in_chunk as u32,
+            index_in_action_receipt: history.index_in_action_receipt as u32,
+        })?;
+        let public_key = history
+            .args
+            .get("public_key")
+            .and_then(|key| key.as_str())
+            .ok_or_else(|| {
+                errors::ErrorKind::InternalError(format!(
+                    "public_key is missing in {} action of receipt {}",
+                    history.cause, history.receipt_id
+                ))
+            })?
+            .to_string();
+
+        Ok(Self {
+            event_index: u128::from(event_index).into(),
+            public_key,
+            permission: get_permission_from_args(&history.args),
+            cause: history.cause,
+            status: history.status,
+            receipt_id: history.receipt_id,
+            transaction_hash: history.transaction_hash,
+            block_timestamp_nanos: block_timestamp_nanos.into(),
+            block_height: types::numeric::to_u64(&history.block_height)?.into(),
+        })
+    }
+}
+
+impl From<near_primitives::views::AccessKeyPermissionView>
+    for accounts::schemas::AccessKeyPermission
+{
+    fn from(permission: near_primitives::views::AccessKeyPermissionView) -> Self {
+        match permission {
+            near_primitives::views::AccessKeyPermissionView::FunctionCall {
+                allowance,
+                receiver_id,
+                method_names,
+            } => Self {
+                kind: "FUNCTION_CALL".to_string(),
+                allowance: allowance.map(types::U128),
+                receiver_account_id: Some(receiver_id),
+                method_names,
+            },
+            near_primitives::views::AccessKeyPermissionView::FullAccess => Self {
+                kind: "FULL_ACCESS".to_string(),
+                allowance: None,
+                receiver_account_id: None,
+                method_names: vec![],
+            },
+        }
+    }
+}
+
+// ADD_KEY args are stored in the DB as
+// {"access_key": {"nonce": 0, "permission": {"permission_kind": "...", "permission_details": {...}}}, "public_key": "..."}
+// DELETE_KEY args contain only public_key
+fn get_permission_from_args(
+    args: &serde_json::Value,
+) -> Option<accounts::schemas::AccessKeyPermission> {
+    let permission = args.get("access_key")?.get("permission")?;
+    let kind = permission.get("permission_kind")?.as_str()?.to_string();
+    let details = permission.get("permission_details");
+    Some(accounts::schemas::AccessKeyPermission {
+        kind,
+        allowance: details
+            .and_then(|details| details.get("allowance"))
+            .and_then(|allowance| allowance.as_str())
+            .and_then(|allowance| allowance.parse::<u128>().ok())
+            .map(types::U128),
+        receiver_account_id: details
+            .and_then(|details| details.get("receiver_id"))
+            .and_then(|receiver_id| receiver_id.as_str())
+            .map(|receiver_id| receiver_id.to_string()),
+        method_names: details
+            .and_then(|details| details.get("method_names"))
+            .and_then(|method_names| method_names.as_array())
+            .map(|method_names| {
+                method_names
+                    .iter()
+                    .filter_map(|name| name.as_str().map(|name| name.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_permission_from_function_call_args() {
+        let args = serde_json::json!({
+            "access_key": {
+                "nonce": 0,
+                "permission": {
+                    "permission_kind": "FUNCTION_CALL",
+                    "permission_details": {
+                        "allowance": "250000000000000000000000",
+                        "receiver_id": "app.nearcrowd.near",
+                        "method_names": ["claim", "withdraw"]
+                    }
+                }
+            },
+            "public_key": "ed25519:8Rn4FJeeRYcrLbcrAQNFVgvbZ2FCEQjgydbXwqBwF1ib"
+        });
+        let permission = get_permission_from_args(&args).unwrap();
+        assert_eq!(permission.kind, "FUNCTION_CALL");
+        assert_eq!(
+            permission.allowance,
+            Some(types::U128(250000000000000000000000))
+        );
+        assert_eq!(
+            permission.receiver_account_id,
+            Some("app.nearcrowd.near".to_string())
+        );
+        assert_eq!(permission.method_names, vec!["claim", "withdraw"]);
+    }
+
+    #[test]
+    fn test_permission_from_delete_key_args() {
+        let args = serde_json::json!({
+            "public_key": "ed25519:8Rn4FJeeRYcrLbcrAQNFVgvbZ2FCEQjgydbXwqBwF1ib"
+        });
+        assert!(get_permission_from_args(&args).is_none());
+    }
+
+    #[test]
+    fn test_access_key_history_item_in_busy_chunk() {
+        let history = super::super::models::AccessKeyHistoryInfo {
+            receipt_id: "CSzRUwEzNJZpNKpSUzCuAZyZ5WuXQmE7nzBo4SUD4Vsu".to_string(),
+            cause: "DELETE_KEY".to_string(),
+            args: serde_json::json!({
+                "public_key": "ed25519:8Rn4FJeeRYcrLbcrAQNFVgvbZ2FCEQjgydbXwqBwF1ib"
+            }),
+            status: "SUCCESS".to_string(),
+            transaction_hash: None,
+            shard_id: crate::BigDecimal::from(3),
+            index_in_chunk: 25_000,
+            index_in_action_receipt: 2,
+            block_timestamp_nanos: crate::BigDecimal::from(1670867680027218116_u64),
+            block_height: crate::BigDecimal::from(80500000),
+        };
+        let item = accounts::schemas::AccessKeyHistoryItem::try_from(history).unwrap();
+        let parts = types::EventIndex::new(item.event_index.0).action_parts();
+        assert_eq!(parts.shard_id, 3);
+        assert_eq!(parts.index_in_chunk, 25_000);
+        assert_eq!(parts.index_in_action_receipt, 2);
+    }
+
+    #[tokio::test]
+    async fn test_access_keys() {
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+
+        let keys = get_access_keys(&rpc_client, &account, &block)
+            .await
+            .unwrap();
+        assert!(!keys.is_empty());
+        assert!(keys.iter().any(|key| key.permission.kind == "FULL_ACCESS"));
+    }
+
+    #[tokio::test]
+    async fn test_access_keys_history() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 5,
+            after_event_index: None,
+        };
+
+        let history = get_access_keys_history(&pool_explorer, &account, &block, &pagination)
+            .await
+            .unwrap();
+        assert!(!history.is_empty());
+        for item in &history {
+            assert!(item.cause == "ADD_KEY" || item.cause == "DELETE_KEY");
+            assert_eq!(item.permission.is_some(), item.cause == "ADD_KEY");
+            assert!(item.block_timestamp_nanos.0 <= block.timestamp);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_access_keys_history_next_page() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 2,
+            after_event_index: None,
+        };
+        let page1 = get_access_keys_history(&pool_explorer, &account, &block, &pagination)
+            .await
+            .unwrap();
+
+        let pagination = types::query_params::Pagination {
+            limit: 2,
+            after_event_index: Some(page1.last().unwrap().event_index.0),
+        };
+        let block = db_helpers::get_block_from_pagination(&pool_explorer, &pagination)
+            .await
+            .unwrap();
+        let page2 = get_access_keys_history(&pool_explorer, &account, &block, &pagination)
+            .await
+            .unwrap();
+
+        assert!(
+            page2
+                .iter()
+                .all(|item| item.event_index < page1.last().unwrap().event_index),
+            "Next page should not include key changes from previous page"
+        );
+    }
+}
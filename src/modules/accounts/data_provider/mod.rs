@@ -0,0 +1,8 @@
+mod account_info;
+mod keys;
+mod models;
+mod storage;
+
+pub(crate) use account_info::get_account;
+pub(crate) use keys::{get_access_keys, get_access_keys_history};
+pub(crate) use storage::{get_storage_balance, get_storage_balances};
@@ -0,0 +1,30 @@
+use crate::BigDecimal;
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct AccessKeyHistoryInfo {
+    pub receipt_id: String,
+    pub cause: String,
+    pub args: serde_json::Value,
+    pub status: String,
+    pub transaction_hash: Option<String>,
+    pub shard_id: BigDecimal,
+    pub index_in_chunk: i32,
+    pub index_in_action_receipt: i32,
+    pub block_timestamp_nanos: BigDecimal,
+    pub block_height: BigDecimal,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct AccountInfo {
+    pub created_by_receipt_id: Option<String>,
+    pub deleted_by_receipt_id: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct AccountChangeInfo {
+    pub receipt_id: String,
+    pub predecessor_account_id: String,
+    pub transaction_hash: Option<String>,
+    pub block_timestamp_nanos: BigDecimal,
+    pub block_height: BigDecimal,
+}
@@ -0,0 +1,195 @@
+use crate::modules::{accounts, ft};
+use crate::{db_helpers, errors, rpc_helpers, types};
+
+#[derive(serde::Deserialize)]
+struct StorageBalanceView {
+    total: types::U128,
+    available: types::U128,
+}
+
+pub(crate) async fn get_storage_balance(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    block: &db_helpers::Block,
+    contract_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+) -> crate::Result<accounts::schemas::StorageBalance> {
+    get_implemented_storage_balance(rpc_client, block, contract_id, account_id)
+        .await?
+        .map_err(errors::ErrorKind::ContractError)?
+        .ok_or_else(|| {
+            errors::ErrorKind::InvalidInput(format!(
+                "The account `{}` does not implement any suitable contract at block {}",
+                contract_id, block.height
+            ))
+            .into()
+        })
+}
+
+/// Returns the storage balances for the contracts from the user's FT balances list.
+/// The contracts not implementing NEP-145 or failing to answer are skipped,
+/// one broken contract should not hide the others
+pub(crate) async fn get_storage_balances(
+    pool_balances: &db_helpers::BalancesPool,
+    rpc_client: &crate::rpc_pool::RpcPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    limit: u32,
+) -> crate::Result<Vec<accounts::schemas::StorageBalance>> {
+    let ft_balances =
+        ft::data_provider::get_ft_balances(pool_balances, rpc_client, account_id, block, limit)
+            .await?;
+
+    let mut result: Vec<accounts::schemas::StorageBalance> = vec![];
+    for ft_balance in ft_balances {
+        match get_implemented_storage_balance(
+            rpc_client,
+            block,
+            &ft_balance.contract_account_id.0,
+            account_id,
+        )
+        .await?
+        {
+            Ok(Some(storage_balance)) => result.push(storage_balance),
+            Ok(None) => {}
+            Err(vm_error) => tracing::warn!(
+                target: crate::LOGGER_MSG,
+                "Skipping storage balance of {} at contract {}, block {}: {}",
+                account_id,
+                ft_balance.contract_account_id.0,
+                block.height,
+                vm_error
+            ),
+        }
+    }
+    Ok(result)
+}
+
+// None if the contract does not implement NEP-145.
+// The contract failure is given as `Ok(Err(vm_error))`, the caller decides whether it's fatal
+async fn get_implemented_storage_balance(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    block: &db_helpers::Block,
+    contract_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+) -> crate::Result<Result<Option<accounts::schemas::StorageBalance>, String>> {
+    let balance = match call_storage_method(
+        rpc_client,
+        block,
+        contract_id,
+        "storage_balance_of",
+        serde_json::json!({ "account_id": account_id }),
+    )
+    .await?
+    {
+        Ok(Some(result)) => serde_json::from_slice::<Option<StorageBalanceView>>(&result)?,
+        Ok(None) => return Ok(Ok(None)),
+        Err(vm_error) => return Ok(Err(vm_error)),
+    };
+    let bounds = match call_storage_method(
+        rpc_client,
+        block,
+        contract_id,
+        "storage_balance_bounds",
+        serde_json::json!({}),
+    )
+    .await?
+    {
+        Ok(Some(result)) => {
+            serde_json::from_slice::<accounts::schemas::StorageBalanceBounds>(&result)?
+        }
+        Ok(None) => return Ok(Ok(None)),
+        Err(vm_error) => return Ok(Err(vm_error)),
+    };
+
+    Ok(Ok(Some(accounts::schemas::StorageBalance {
+        contract_account_id: contract_id.clone().into(),
+        is_registered: balance.is_some(),
+        total: balance.as_ref().map(|balance| balance.total),
+        available: balance.as_ref().map(|balance| balance.available),
+        bounds,
+    })))
+}
+
+async fn call_storage_method(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    block: &db_helpers::Block,
+    contract_id: &near_primitives::types::AccountId,
+    method_name: &str,
+    args: serde_json::Value,
+) -> crate::Result<Result<Option<Vec<u8>>, String>> {
+    let request = rpc_helpers::get_function_call_request(
+        block.height,
+        contract_id.clone(),
+        method_name,
+        args,
+    );
+    Ok(
+        match rpc_helpers::call_function(rpc_client, request, block.height, contract_id).await? {
+            rpc_helpers::FunctionCallResult::Success(response) => Ok(Some(response.result)),
+            rpc_helpers::FunctionCallResult::NotImplemented => Ok(None),
+            rpc_helpers::FunctionCallResult::Failed(vm_error) => Err(vm_error),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn test_storage_balance() {
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("usn").unwrap();
+        let account = near_primitives::types::AccountId::from_str("olga.near").unwrap();
+
+        let storage_balance = get_storage_balance(&rpc_client, &block, &contract, &account)
+            .await
+            .unwrap();
+        assert!(storage_balance.is_registered);
+        assert!(storage_balance.total.unwrap().0 >= storage_balance.bounds.min.0);
+    }
+
+    #[tokio::test]
+    async fn test_storage_balance_not_registered() {
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("usn").unwrap();
+        let account =
+            near_primitives::types::AccountId::from_str("two-idiots-and-a-half.near").unwrap();
+
+        let storage_balance = get_storage_balance(&rpc_client, &block, &contract, &account)
+            .await
+            .unwrap();
+        assert!(!storage_balance.is_registered);
+        assert!(storage_balance.total.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_storage_balance_no_contract_deployed() {
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("olga.near").unwrap();
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+
+        let storage_balance = get_storage_balance(&rpc_client, &block, &contract, &account).await;
+        assert_eq!(storage_balance.unwrap_err().code, 400);
+    }
+
+    #[tokio::test]
+    async fn test_storage_balances() {
+        let pool_balances = init_balances_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("olga.near").unwrap();
+
+        let storage_balances =
+            get_storage_balances(&pool_balances, &rpc_client, &account, &block, 10)
+                .await
+                .unwrap();
+        assert!(!storage_balances.is_empty());
+        assert!(storage_balances.iter().all(|balance| balance.is_registered));
+    }
+}
@@ -0,0 +1,27 @@
+use paperclip::actix::web;
+
+mod data_provider;
+mod resources;
+mod schemas;
+
+pub(crate) fn register_services(app: &mut web::ServiceConfig) {
+    app.service(
+        web::resource("/accounts/{account_id}").route(web::get().to(resources::get_account)),
+    )
+    .service(
+        web::resource("/accounts/{account_id}/keys")
+            .route(web::get().to(resources::get_access_keys)),
+    )
+    .service(
+        web::resource("/accounts/{account_id}/keys/history")
+            .route(web::get().to(resources::get_access_keys_history)),
+    )
+    .service(
+        web::resource("/accounts/{account_id}/storage")
+            .route(web::get().to(resources::get_storage_balances)),
+    )
+    .service(
+        web::resource("/accounts/{account_id}/storage/{contract_account_id}")
+            .route(web::get().to(resources::get_storage_balance)),
+    );
+}
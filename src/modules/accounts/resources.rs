@@ -0,0 +1,158 @@
+use paperclip::actix::{
+    api_v2_operation,
+    web::{self, Json},
+};
+
+use super::{data_provider, schemas};
+use crate::{db_helpers, modules, types};
+
+#[api_v2_operation(tags(Accounts))]
+/// Get account info
+///
+/// This endpoint returns the balance, the contract code hash and the storage usage
+/// of the given `account_id` at the given `block_timestamp_nanos`/`block_height`.
+/// It also shows when and by whom the account was created and deleted.
+pub async fn get_account(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::AccountRequest>,
+    block_params: web::Query<types::query_params::BlockParams>,
+) -> crate::Result<Json<schemas::AccountResponse>> {
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
+
+    Ok(Json(schemas::AccountResponse {
+        account: data_provider::get_account(
+            &pool_explorer,
+            &rpc_client,
+            &request.account_id.0,
+            &block,
+        )
+        .await?,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+        finality: block.finality,
+    }))
+}
+
+#[api_v2_operation(tags(Accounts))]
+/// Get user's access keys
+///
+/// This endpoint returns the list of access keys of the given `account_id`
+/// at the given `block_timestamp_nanos`/`block_height`.
+/// For function call keys, it also shows the allowance, the receiver and the allowed methods.
+pub async fn get_access_keys(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::AccountRequest>,
+    block_params: web::Query<types::query_params::BlockParams>,
+) -> crate::Result<Json<schemas::AccessKeysResponse>> {
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
+    modules::check_account_exists(&rpc_client, &request.account_id.0, block.height).await?;
+
+    Ok(Json(schemas::AccessKeysResponse {
+        keys: data_provider::get_access_keys(&rpc_client, &request.account_id.0, &block).await?,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+        finality: block.finality,
+    }))
+}
+
+#[api_v2_operation(tags(Accounts))]
+/// Get user's access keys history
+///
+/// This endpoint returns the history of adding and deleting the access keys
+/// for the given `account_id`.
+/// For the next page, use `event_index` of the last item in your previous response.
+pub async fn get_access_keys_history(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::AccountRequest>,
+    pagination_params: web::Query<types::query_params::PaginationParams>,
+) -> crate::Result<Json<schemas::AccessKeysHistoryResponse>> {
+    let pagination = modules::checked_get_pagination_params(&pagination_params).await?;
+    let block = db_helpers::get_block_from_pagination(&pool_explorer, &pagination).await?;
+    // we don't need to check whether account exists. If not, we can just return the empty history
+
+    Ok(Json(schemas::AccessKeysHistoryResponse {
+        history: data_provider::get_access_keys_history(
+            &pool_explorer,
+            &request.account_id.0,
+            &block,
+            &pagination,
+        )
+        .await?,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+    }))
+}
+
+#[api_v2_operation(tags(Accounts))]
+/// Get user's storage balance by contract
+///
+/// This endpoint returns the storage balance (NEP-145) of the given `account_id`
+/// for the given `contract_account_id` and `block_timestamp_nanos`/`block_height`.
+/// Use it to check whether the user is registered at the contract before sending the tokens.
+pub async fn get_storage_balance(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::StorageBalanceRequest>,
+    block_params: web::Query<types::query_params::BlockParams>,
+) -> crate::Result<Json<schemas::StorageBalanceResponse>> {
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
+    // we don't need to check whether account exists.
+    // The contract knows nothing about unknown account, so we show it's not registered
+
+    Ok(Json(schemas::StorageBalanceResponse {
+        storage_balance: data_provider::get_storage_balance(
+            &rpc_client,
+            &block,
+            &request.contract_account_id.0,
+            &request.account_id.0,
+        )
+        .await?,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+        finality: block.finality,
+    }))
+}
+
+#[api_v2_operation(tags(Accounts))]
+/// Get user's storage balances
+///
+/// This endpoint returns the storage balances (NEP-145) of the given `account_id`
+/// for all the FT contracts where the user has non-zero balance,
+/// at the given `block_timestamp_nanos`/`block_height`.
+/// The contracts which do not implement NEP-145 or fail to answer are skipped.
+///
+/// **Limitations**
+/// This endpoint scans all the FT contracts, `limit` is applied to the list of FT balances.
+pub async fn get_storage_balances(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    pool_balances: web::Data<db_helpers::BalancesPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::AccountRequest>,
+    block_params: web::Query<types::query_params::BlockParams>,
+    limit_params: web::Query<types::query_params::LimitParams>,
+) -> crate::Result<Json<schemas::StorageBalancesResponse>> {
+    let limit = types::query_params::checked_get_limit(limit_params.limit)?;
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
+    modules::check_account_exists(&rpc_client, &request.account_id.0, block.height).await?;
+
+    Ok(Json(schemas::StorageBalancesResponse {
+        storage_balances: data_provider::get_storage_balances(
+            &pool_balances,
+            &rpc_client,
+            &request.account_id.0,
+            &block,
+            limit,
+        )
+        .await?,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+        finality: block.finality,
+    }))
+}
@@ -0,0 +1,149 @@
+use paperclip::actix::Apiv2Schema;
+use validator::Validate;
+
+use crate::types;
+
+// *** Requests ***
+
+#[derive(
+    Validate, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema,
+)]
+pub struct AccountRequest {
+    #[validate(custom = "crate::errors::validate_account_id")]
+    pub account_id: types::AccountId,
+}
+
+#[derive(
+    Validate, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema,
+)]
+pub struct StorageBalanceRequest {
+    #[validate(custom = "crate::errors::validate_account_id")]
+    pub account_id: types::AccountId,
+    #[validate(custom = "crate::errors::validate_account_id")]
+    pub contract_account_id: types::AccountId,
+}
+
+// *** Responses ***
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct AccountResponse {
+    pub account: Account,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct AccessKeysResponse {
+    pub keys: Vec<AccessKey>,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct AccessKeysHistoryResponse {
+    pub history: Vec<AccessKeyHistoryItem>,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct StorageBalanceResponse {
+    pub storage_balance: StorageBalance,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct StorageBalancesResponse {
+    pub storage_balances: Vec<StorageBalance>,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
+}
+
+// ---
+
+/// `amount`, `locked`, `code_hash`, `storage_usage` show the state at the requested block,
+/// they are empty if the account is already deleted at the requested block.
+/// `code_hash` is `11111111111111111111111111111111` if there is no contract deployed.
+/// `created_by` is empty for the genesis accounts.
+/// `deleted_by` is provided if the account was deleted, even if it happened after the requested block
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct Account {
+    pub account_id: types::AccountId,
+    pub amount: Option<types::U128>,
+    pub locked: Option<types::U128>,
+    pub code_hash: Option<String>,
+    pub storage_usage: Option<types::U64>,
+    pub created_by: Option<AccountChange>,
+    pub deleted_by: Option<AccountChange>,
+}
+
+/// This type describes the receipt which created or deleted the account.
+/// `predecessor_account_id` is the creator of the account for the account creation
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct AccountChange {
+    pub predecessor_account_id: types::AccountId,
+    pub receipt_id: String,
+    pub transaction_hash: Option<String>,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct AccessKey {
+    pub public_key: String,
+    pub nonce: types::U64,
+    pub permission: AccessKeyPermission,
+}
+
+/// `kind` is one of ["FULL_ACCESS", "FUNCTION_CALL"].
+/// `allowance`, `receiver_account_id`, `method_names` are provided only for FUNCTION_CALL keys,
+/// `allowance` is empty if the key is allowed to spend any amount of NEAR for the gas,
+/// `method_names` is empty if the key is allowed to call any method of `receiver_account_id`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct AccessKeyPermission {
+    pub kind: String,
+    pub allowance: Option<types::U128>,
+    pub receiver_account_id: Option<String>,
+    pub method_names: Vec<String>,
+}
+
+/// This type describes the history of the access keys of the account.
+/// `cause` is one of ["ADD_KEY", "DELETE_KEY"], `permission` is provided only for ADD_KEY.
+/// Failed receipts are also included, check `status` to see whether the key was actually changed
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct AccessKeyHistoryItem {
+    pub event_index: types::U128,
+    pub public_key: String,
+    pub cause: String,
+    pub permission: Option<AccessKeyPermission>,
+    pub status: String,
+    pub receipt_id: String,
+    pub transaction_hash: Option<String>,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
+
+/// This type describes the storage balance of the user at the contract. Inspired by
+/// https://nomicon.io/Standards/StorageManagement
+/// `total`, `available` are provided only if the user is registered at the contract
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct StorageBalance {
+    pub contract_account_id: types::AccountId,
+    pub is_registered: bool,
+    pub total: Option<types::U128>,
+    pub available: Option<types::U128>,
+    pub bounds: StorageBalanceBounds,
+}
+
+/// `min` is the amount required to register at the contract,
+/// `max` is empty if the contract accepts any deposit for the storage
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct StorageBalanceBounds {
+    pub min: types::U128,
+    pub max: Option<types::U128>,
+}
@@ -0,0 +1,290 @@
+use crate::modules::contracts;
+use crate::{db_helpers, errors, modules, rpc_helpers, types};
+
+// Each standard is detected by the view method which is required by the standard
+// and does not need any specific args
+fn get_standards_view_methods() -> [(&'static str, &'static str, serde_json::Value); 5] {
+    [
+        ("nep141", "ft_total_supply", serde_json::json!({})),
+        ("nep145", "storage_balance_bounds", serde_json::json!({})),
+        ("nep171", "nft_token", serde_json::json!({ "token_id": "" })),
+        ("nep245", "mt_token", serde_json::json!({ "token_ids": [] })),
+        ("nep330", "contract_source_metadata", serde_json::json!({})),
+    ]
+}
+
+pub(crate) async fn get_contract(
+    pool_explorer: &db_helpers::ExplorerPool,
+    pool_balances: &db_helpers::BalancesPool,
+    rpc_client: &crate::rpc_pool::RpcPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    limit: u32,
+) -> crate::Result<contracts::schemas::Contract> {
+    let account_view = modules::get_account_view(rpc_client, account_id, block.height).await?;
+    let has_code = account_view.code_hash != near_primitives::hash::CryptoHash::default();
+
+    let standards_view_methods = get_standards_view_methods();
+    // The probes are independent, there is no need to wait for each of them
+    let implemented_view_methods =
+        futures::future::try_join_all(standards_view_methods.iter().map(
+            |(_, method_name, args)| async move {
+                Ok::<bool, crate::errors::Error>(
+                    has_code
+                        && rpc_helpers::is_method_implemented(
+                            rpc_client,
+                            block.height,
+                            account_id,
+                            method_name,
+                            args.clone(),
+                        )
+                        .await?,
+                )
+            },
+        ))
+        .await?;
+
+    let mut standards: Vec<contracts::schemas::ContractStandard> = vec![];
+    for ((standard, _, _), implements_view_methods) in standards_view_methods
+        .into_iter()
+        .zip(implemented_view_methods)
+    {
+        let emits_events = match standard {
+            "nep141" => has_ft_events(pool_balances, account_id, block).await?,
+            "nep171" => has_nft_events(pool_explorer, account_id, block).await?,
+            _ => false,
+        };
+        if implements_view_methods || emits_events {
+            standards.push(contracts::schemas::ContractStandard {
+                standard: standard.to_string(),
+                implements_view_methods,
+                emits_events,
+            });
+        }
+    }
+
+    Ok(contracts::schemas::Contract {
+        account_id: account_id.clone().into(),
+        code_hash: account_view.code_hash.to_string(),
+        standards,
+        deployments: get_deployments(pool_explorer, account_id, block, limit).await?,
+    })
+}
+
+async fn get_deployments(
+    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    limit: u32,
+) -> crate::Result<Vec<contracts::schemas::ContractDeployment>> {
+    let query = r"
+        SELECT
+            action_receipt_actions.args,
+            CASE WHEN execution_outcomes.status IN ('SUCCESS_VALUE', 'SUCCESS_RECEIPT_ID') THEN 'SUCCESS'
+                WHEN execution_outcomes.status = 'FAILURE' THEN 'FAILURE'
+                ELSE 'UNKNOWN'
+            END status,
+            action_receipt_actions.receipt_id,
+            receipts.originated_from_transaction_hash transaction_hash,
+            receipts.included_in_block_timestamp block_timestamp_nanos,
+            blocks.block_height
+        FROM action_receipt_actions
+            JOIN receipts ON action_receipt_actions.receipt_id = receipts.receipt_id
+            JOIN blocks ON receipts.included_in_block_hash = blocks.block_hash
+            LEFT JOIN execution_outcomes ON action_receipt_actions.receipt_id = execution_outcomes.receipt_id
+        WHERE action_receipt_actions.receipt_receiver_account_id = $1
+            AND action_receipt_actions.action_kind = 'DEPLOY_CONTRACT'
+            AND receipts.included_in_block_timestamp <= $2::numeric(20, 0)
+        ORDER BY receipts.included_in_block_timestamp DESC
+        LIMIT $3::numeric(20, 0)
+    ";
+    let deployments_info =
+        db_helpers::select_retry_or_panic::<super::models::ContractDeploymentInfo>(
+            pool_explorer,
+            query,
+            &[
+                account_id.to_string(),
+                block.timestamp.to_string(),
+                limit.to_string(),
+            ],
+        )
+        .await?;
+
+    let mut result: Vec<contracts::schemas::ContractDeployment> = vec![];
+    for info in deployments_info {
+        result.push(info.try_into()?);
+    }
+    Ok(result)
+}
+
+async fn has_ft_events(
+    db_helpers::BalancesPool(pool_balances): &db_helpers::BalancesPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+) -> crate::Result<bool> {
+    let query = r"
+        SELECT EXISTS (
+            SELECT 1
+            FROM fungible_token_events
+            WHERE contract_account_id = $1 AND block_timestamp <= $2::numeric(20, 0)
+        ) exists
+    ";
+    events_exist(pool_balances, query, account_id, block).await
+}
+
+async fn has_nft_events(
+    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+) -> crate::Result<bool> {
+    let query = r"
+        SELECT EXISTS (
+            SELECT 1
+            FROM assets__non_fungible_token_events
+            WHERE emitted_by_contract_account_id = $1 AND emitted_at_block_timestamp <= $2::numeric(20, 0)
+        ) exists
+    ";
+    events_exist(pool_explorer, query, account_id, block).await
+}
+
+async fn events_exist(
+    pool: &sqlx::Pool<sqlx::Postgres>,
+    query: &str,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+) -> crate::Result<bool> {
+    let result = db_helpers::select_retry_or_panic::<super::models::EventsExist>(
+        pool,
+        query,
+        &[account_id.to_string(), block.timestamp.to_string()],
+    )
+    .await?;
+    Ok(result.first().map(|row| row.exists).unwrap_or_default())
+}
+
+// The DB keeps the hex-encoded sha256 of the code, RPC shows the same hash in base58
+fn get_code_hash_from_args(args: &serde_json::Value) -> Option<String> {
+    let code_sha256 = hex::decode(args.get("code_sha256")?.as_str()?).ok()?;
+    near_primitives::hash::CryptoHash::try_from(code_sha256.as_slice())
+        .ok()
+        .map(|hash| hash.to_string())
+}
+
+impl TryFrom<super::models::ContractDeploymentInfo> for contracts::schemas::ContractDeployment {
+    type Error = errors::Error;
+
+    fn try_from(info: super::models::ContractDeploymentInfo) -> crate::Result<Self> {
+        Ok(Self {
+            code_hash: get_code_hash_from_args(&info.args),
+            status: info.status,
+            receipt_id: info.receipt_id,
+            transaction_hash: info.transaction_hash,
+            block_timestamp_nanos: types::numeric::to_u64(&info.block_timestamp_nanos)?.into(),
+            block_height: types::numeric::to_u64(&info.block_height)?.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_code_hash_from_args() {
+        let args = serde_json::json!({
+            "code_sha256": "3d8fa8b2f2f1b1bc4a1f11d25ef2a6cd4ee8f19b7da4ea75c3c0ac1a6a5de2e4"
+        });
+        let code_hash = get_code_hash_from_args(&args).unwrap();
+        assert_eq!(
+            near_primitives::hash::CryptoHash::from_str(&code_hash)
+                .unwrap()
+                .0
+                .to_vec(),
+            hex::decode("3d8fa8b2f2f1b1bc4a1f11d25ef2a6cd4ee8f19b7da4ea75c3c0ac1a6a5de2e4")
+                .unwrap()
+        );
+        assert!(get_code_hash_from_args(&serde_json::json!({})).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ft_contract() {
+        let pool_explorer = init_explorer_db().await;
+        let pool_balances = init_balances_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("usn").unwrap();
+
+        let contract_info = get_contract(
+            &pool_explorer,
+            &pool_balances,
+            &rpc_client,
+            &contract,
+            &block,
+            5,
+        )
+        .await
+        .unwrap();
+        assert!(!contract_info.deployments.is_empty());
+        assert!(contract_info
+            .standards
+            .iter()
+            .any(|standard| standard.standard == "nep141" && standard.implements_view_methods));
+        assert!(!contract_info
+            .standards
+            .iter()
+            .any(|standard| standard.standard == "nep171"));
+    }
+
+    #[tokio::test]
+    async fn test_nft_contract() {
+        let pool_explorer = init_explorer_db().await;
+        let pool_balances = init_balances_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+
+        let contract_info = get_contract(
+            &pool_explorer,
+            &pool_balances,
+            &rpc_client,
+            &contract,
+            &block,
+            5,
+        )
+        .await
+        .unwrap();
+        assert!(contract_info
+            .standards
+            .iter()
+            .any(|standard| standard.standard == "nep171"
+                && standard.implements_view_methods
+                && standard.emits_events));
+    }
+
+    #[tokio::test]
+    async fn test_account_without_contract() {
+        let pool_explorer = init_explorer_db().await;
+        let pool_balances = init_balances_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("olga.near").unwrap();
+
+        let contract_info = get_contract(
+            &pool_explorer,
+            &pool_balances,
+            &rpc_client,
+            &account,
+            &block,
+            5,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            contract_info.code_hash,
+            near_primitives::hash::CryptoHash::default().to_string()
+        );
+        assert!(contract_info.standards.is_empty());
+    }
+}
@@ -0,0 +1,4 @@
+mod contract_info;
+mod models;
+
+pub(crate) use contract_info::get_contract;
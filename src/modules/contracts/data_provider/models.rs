@@ -0,0 +1,16 @@
+use crate::BigDecimal;
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct ContractDeploymentInfo {
+    pub args: serde_json::Value,
+    pub status: String,
+    pub receipt_id: String,
+    pub transaction_hash: Option<String>,
+    pub block_timestamp_nanos: BigDecimal,
+    pub block_height: BigDecimal,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct EventsExist {
+    pub exists: bool,
+}
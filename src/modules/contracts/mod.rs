@@ -0,0 +1,11 @@
+use paperclip::actix::web;
+
+mod data_provider;
+mod resources;
+mod schemas;
+
+pub(crate) fn register_services(app: &mut web::ServiceConfig) {
+    app.service(
+        web::resource("/contracts/{account_id}").route(web::get().to(resources::get_contract)),
+    );
+}
@@ -0,0 +1,42 @@
+use paperclip::actix::{
+    api_v2_operation,
+    web::{self, Json},
+};
+
+use super::{data_provider, schemas};
+use crate::{db_helpers, types};
+
+#[api_v2_operation(tags(Contracts))]
+/// Get contract info
+///
+/// This endpoint returns the code hash and the deployment history of the contract
+/// deployed to the given `account_id` at the given `block_timestamp_nanos`/`block_height`.
+/// It also detects the standards implemented by the contract.
+/// `limit` is applied to the deployment history.
+pub async fn get_contract(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    pool_balances: web::Data<db_helpers::BalancesPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::ContractRequest>,
+    block_params: web::Query<types::query_params::BlockParams>,
+    limit_params: web::Query<types::query_params::LimitParams>,
+) -> crate::Result<Json<schemas::ContractResponse>> {
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
+    let limit = types::query_params::checked_get_limit(limit_params.limit)?;
+
+    Ok(Json(schemas::ContractResponse {
+        contract: data_provider::get_contract(
+            &pool_explorer,
+            &pool_balances,
+            &rpc_client,
+            &request.account_id.0,
+            &block,
+            limit,
+        )
+        .await?,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+        finality: block.finality,
+    }))
+}
@@ -0,0 +1,59 @@
+use paperclip::actix::Apiv2Schema;
+use validator::Validate;
+
+use crate::types;
+
+// *** Requests ***
+
+#[derive(
+    Validate, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema,
+)]
+pub struct ContractRequest {
+    #[validate(custom = "crate::errors::validate_account_id")]
+    pub account_id: types::AccountId,
+}
+
+// *** Responses ***
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct ContractResponse {
+    pub contract: Contract,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
+}
+
+// ---
+
+/// `code_hash` is `11111111111111111111111111111111` if there is no contract deployed.
+/// `deployments` go from the latest to the oldest one
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct Contract {
+    pub account_id: types::AccountId,
+    pub code_hash: String,
+    pub standards: Vec<ContractStandard>,
+    pub deployments: Vec<ContractDeployment>,
+}
+
+/// `standard` is one of ["nep141", "nep145", "nep171", "nep245", "nep330"].
+/// `implements_view_methods` shows whether the contract has the view methods of the standard
+/// at the requested block.
+/// `emits_events` shows whether we have seen the events of the standard from this contract.
+/// We collect the events only for nep141, nep171
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct ContractStandard {
+    pub standard: String,
+    pub implements_view_methods: bool,
+    pub emits_events: bool,
+}
+
+/// `code_hash` could be empty for the failed deployments
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct ContractDeployment {
+    pub code_hash: Option<String>,
+    pub status: String,
+    pub receipt_id: String,
+    pub transaction_hash: Option<String>,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
@@ -4,7 +4,7 @@ use std::str::FromStr;
 
 pub(crate) async fn get_ft_balances(
     db_helpers::BalancesPool(pool_balances): &db_helpers::BalancesPool,
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     account_id: &near_primitives::types::AccountId,
     block: &db_helpers::Block,
     // TODO pagination
@@ -50,7 +50,7 @@ pub(crate) async fn get_ft_balances(
 }
 
 pub(crate) async fn get_ft_balance_by_contract(
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     block: &db_helpers::Block,
     contract_id: &near_primitives::types::AccountId,
     account_id: &near_primitives::types::AccountId,
@@ -79,7 +79,7 @@ pub(crate) async fn get_ft_balance_by_contract(
 }
 
 pub(crate) async fn get_ft_amount(
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     contract_id: near_primitives::types::AccountId,
     account_id: near_primitives::types::AccountId,
     block_height: u64,
@@ -1,27 +1,62 @@
 use crate::modules::ft;
-use crate::{db_helpers, errors, types};
+use crate::{cache, db_helpers, errors, types};
 use num_traits::{Signed, ToPrimitive};
 use sqlx::types::BigDecimal;
 use std::str::FromStr;
 
+/// The history at the old blocks never changes, so it is cached (see `cache::cached`)
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn get_ft_history(
-    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    pool_explorer: &db_helpers::ExplorerPool,
+    pool_balances: &db_helpers::BalancesPool,
+    rpc_client: &crate::rpc_pool::RpcPool,
+    contract_id: &near_primitives::types::AccountId,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    pagination: &types::query_params::Pagination,
+    with_transaction_info: bool,
+) -> crate::Result<Vec<ft::schemas::HistoryItem>> {
+    cache::cached(
+        "get_ft_history",
+        format!(
+            "{}|{}|{:?}|{}",
+            contract_id, account_id, pagination, with_transaction_info
+        ),
+        block.height,
+        load_ft_history(
+            pool_explorer,
+            pool_balances,
+            rpc_client,
+            contract_id,
+            account_id,
+            block,
+            pagination,
+            with_transaction_info,
+        ),
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn load_ft_history(
+    pool_explorer: &db_helpers::ExplorerPool,
     db_helpers::BalancesPool(pool_balances): &db_helpers::BalancesPool,
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     contract_id: &near_primitives::types::AccountId,
     account_id: &near_primitives::types::AccountId,
     block: &db_helpers::Block,
     pagination: &types::query_params::Pagination,
+    with_transaction_info: bool,
 ) -> crate::Result<Vec<ft::schemas::HistoryItem>> {
     let metadata = ft::schemas::Metadata::from(
         super::metadata::get_ft_metadata(rpc_client, contract_id.clone(), block.height).await?,
     );
 
     let after_event_index = if let Some(index) = pagination.after_event_index {
-        index
+        types::EventIndex::new(index)
     } else {
         // +1 because we need to include given timestamp to result. Query has strict less operator
-        db_helpers::timestamp_to_event_index(block.timestamp + 1)
+        types::EventIndex::from_timestamp(block.timestamp + 1)
     };
 
     // We don't have absolute_value in the DB.
@@ -63,7 +98,8 @@ pub(crate) async fn get_ft_history(
              cause,
              status,
              block_timestamp block_timestamp_nanos,
-             block_height
+             block_height,
+             receipt_id
          FROM fungible_token_events, timestamps
          WHERE contract_account_id = $1
              AND affected_account_id = $2
@@ -105,8 +141,18 @@ pub(crate) async fn get_ft_history(
         return Ok(vec![]);
     };
 
+    let receipt_transactions = db_helpers::get_receipt_transactions(
+        pool_explorer,
+        history
+            .iter()
+            .map(|db_info| db_info.receipt_id.clone())
+            .collect(),
+        with_transaction_info,
+    )
+    .await?;
+
     let mut result: Vec<ft::schemas::HistoryItem> = vec![];
-    for db_info in history {
+    for (db_info, receipt_transaction) in history.into_iter().zip(receipt_transactions) {
         let balance = current_balance.clone();
 
         if db_info.status == "SUCCESS" {
@@ -152,12 +198,16 @@ pub(crate) async fn get_ft_history(
                 block_height: types::numeric::to_u64(&db_info.block_height)?.into(),
                 status: db_info.status,
                 metadata: metadata.clone(),
+                event_index_parts: None,
+                receipt_id: receipt_transaction.receipt_id,
+                transaction_hash: receipt_transaction.transaction_hash,
+                signer_account_id: receipt_transaction.signer_account_id,
             });
         }
     }
 
     let prev_block = if let Some(item) = result.last() {
-        db_helpers::get_previous_block(pool_explorer, item.block_timestamp_nanos.0).await?
+        db_helpers::get_previous_block(&pool_explorer.0, item.block_timestamp_nanos.0).await?
     } else {
         return Ok(result);
     };
@@ -227,11 +277,48 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await;
         insta::assert_debug_snapshot!(balance);
     }
 
+    #[tokio::test]
+    async fn test_ft_history_with_transaction_info() {
+        let pool_explorer = init_explorer_db().await;
+        let pool_balances = init_balances_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str(
+            "aaaaaa20d9e0e2461697782ef11675f668207961.factory.bridge.near",
+        )
+        .unwrap();
+        let account = near_primitives::types::AccountId::from_str("aurora").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 5,
+            after_event_index: None,
+        };
+
+        let history = get_ft_history(
+            &pool_explorer,
+            &pool_balances,
+            &rpc_client,
+            &contract,
+            &account,
+            &block,
+            &pagination,
+            true,
+        )
+        .await
+        .unwrap();
+        assert!(!history.is_empty());
+        for item in history {
+            assert!(item.receipt_id.is_some());
+            assert!(item.transaction_hash.is_some());
+            assert!(item.signer_account_id.is_some());
+        }
+    }
+
     #[tokio::test]
     async fn test_ft_history_next_page() {
         let pool_explorer = init_explorer_db().await;
@@ -258,6 +345,7 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await;
         insta::assert_debug_snapshot!(history);
@@ -289,6 +377,7 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await
         .unwrap();
@@ -308,6 +397,7 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await
         .unwrap();
@@ -347,6 +437,7 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await;
         insta::assert_debug_snapshot!(balance);
@@ -374,6 +465,7 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await
         .unwrap();
@@ -402,6 +494,7 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await
         .unwrap();
@@ -430,6 +523,7 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await;
         insta::assert_debug_snapshot!(balance);
@@ -459,6 +553,7 @@ mod tests {
             &account,
             &block,
             &pagination,
+            false,
         )
         .await;
         insta::assert_debug_snapshot!(balance);
@@ -3,7 +3,7 @@ use crate::rpc_helpers;
 
 // todo switch from rpc to db
 pub(crate) async fn get_ft_metadata(
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     contract_id: near_primitives::types::AccountId,
     block_height: u64,
 ) -> crate::Result<ft::schemas::FtContractMetadata> {
@@ -9,4 +9,5 @@ pub(crate) struct FtHistoryInfo {
     pub status: String,
     pub block_timestamp_nanos: BigDecimal,
     pub block_height: BigDecimal,
+    pub receipt_id: Option<String>,
 }
@@ -1,6 +1,6 @@
 use paperclip::actix::web;
 
-mod data_provider;
+pub(crate) mod data_provider;
 mod resources;
 mod schemas;
 
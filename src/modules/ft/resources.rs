@@ -19,7 +19,7 @@ use crate::{db_helpers, errors, modules, types};
 pub async fn get_ft_balances(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
     pool_balances: web::Data<db_helpers::BalancesPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::BalanceRequest>,
     block_params: web::Query<types::query_params::BlockParams>,
@@ -27,7 +27,7 @@ pub async fn get_ft_balances(
     limit_params: web::Query<types::query_params::LimitParams>,
 ) -> crate::Result<Json<schemas::FtBalancesResponse>> {
     let limit = types::query_params::checked_get_limit(limit_params.limit)?;
-    let block = db_helpers::checked_get_block(&pool_explorer, &block_params).await?;
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
     modules::check_account_exists(&rpc_client, &request.account_id.0, block.height).await?;
 
     let balances = data_provider::get_ft_balances(
@@ -43,6 +43,7 @@ pub async fn get_ft_balances(
         balances,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
+        finality: block.finality,
     }))
 }
 
@@ -53,7 +54,7 @@ pub async fn get_ft_balances(
 /// for the given `contract_account_id` and `block_timestamp_nanos`/`block_height`.
 pub async fn get_ft_balance_by_contract(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::BalanceByContractRequest>,
     block_params: web::Query<types::query_params::BlockParams>,
@@ -64,7 +65,7 @@ pub async fn get_ft_balance_by_contract(
         )
         .into());
     }
-    let block = db_helpers::checked_get_block(&pool_explorer, &block_params).await?;
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
     modules::check_account_exists(&rpc_client, &request.account_id.0, block.height).await?;
 
     let balance = data_provider::get_ft_balance_by_contract(
@@ -79,6 +80,7 @@ pub async fn get_ft_balance_by_contract(
         balance,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
+        finality: block.finality,
     }))
 }
 
@@ -88,13 +90,16 @@ pub async fn get_ft_balance_by_contract(
 /// This endpoint returns the history of FT operations
 /// for the given `account_id`, `contract_account_id`.
 /// For the next page, use `event_index` of the last item in your previous response.
+/// Set `with_event_index_parts` to get the decoded `event_index` for each item.
+/// Set `skip_transaction_info` if you don't need the transaction for each item, it makes the response faster.
 pub async fn get_ft_history(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
     pool_balances: web::Data<db_helpers::BalancesPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::HistoryRequest>,
     pagination_params: web::Query<types::query_params::PaginationParams>,
+    history_params: web::Query<types::query_params::HistoryParams>,
 ) -> crate::Result<Json<schemas::FtHistoryResponse>> {
     if request.contract_account_id.to_string() == "near" {
         return Err(errors::ErrorKind::InvalidInput(
@@ -107,17 +112,25 @@ pub async fn get_ft_history(
     let block = db_helpers::get_block_from_pagination(&pool_explorer, &pagination).await?;
     // we don't need to check whether account exists. If not, we can just return the empty history
 
+    let mut history = data_provider::get_ft_history(
+        &pool_explorer,
+        &pool_balances,
+        &rpc_client,
+        &request.contract_account_id.0,
+        &request.account_id.0,
+        &block,
+        &pagination,
+        !history_params.skip_transaction_info.unwrap_or_default(),
+    )
+    .await?;
+    if history_params.with_event_index_parts.unwrap_or_default() {
+        for item in history.iter_mut() {
+            item.event_index_parts = Some(types::EventIndex::new(item.event_index.0).asset_parts());
+        }
+    }
+
     Ok(Json(schemas::FtHistoryResponse {
-        history: data_provider::get_ft_history(
-            &pool_explorer,
-            &pool_balances,
-            &rpc_client,
-            &request.contract_account_id.0,
-            &request.account_id.0,
-            &block,
-            &pagination,
-        )
-        .await?,
+        history,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
     }))
@@ -129,12 +142,12 @@ pub async fn get_ft_history(
 /// This endpoint returns the metadata for the given `contract_account_id`, `block_timestamp_nanos`/`block_height`.
 pub async fn get_ft_metadata(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::ContractMetadataRequest>,
     block_params: web::Query<types::query_params::BlockParams>,
 ) -> crate::Result<Json<schemas::FtContractMetadataResponse>> {
-    let block = db_helpers::checked_get_block(&pool_explorer, &block_params).await?;
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
 
     Ok(Json(schemas::FtContractMetadataResponse {
         metadata: data_provider::get_ft_metadata(
@@ -145,5 +158,6 @@ pub async fn get_ft_metadata(
         .await?,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
+        finality: block.finality,
     }))
 }
@@ -1,14 +1,43 @@
-use crate::{errors, types};
+use crate::{deadline, errors, types};
 
+pub(crate) mod accounts;
+pub(crate) mod contracts;
 pub(crate) mod ft;
 pub(crate) mod native;
 pub(crate) mod nft;
+pub(crate) mod status;
+pub(crate) mod transactions;
 
 pub(crate) async fn check_account_exists(
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     account_id: &near_primitives::types::AccountId,
     block_height: u64,
 ) -> crate::Result<()> {
+    get_account_view(rpc_client, account_id, block_height).await?;
+    Ok(())
+}
+
+/// Returns the state of the account at the given block.
+/// Fails with InvalidInput if the account does not exist at this block
+pub(crate) async fn get_account_view(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    account_id: &near_primitives::types::AccountId,
+    block_height: u64,
+) -> crate::Result<near_primitives::views::AccountView> {
+    crate::cache::cached(
+        "view_account",
+        account_id.to_string(),
+        block_height,
+        view_account(rpc_client, account_id, block_height),
+    )
+    .await
+}
+
+async fn view_account(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    account_id: &near_primitives::types::AccountId,
+    block_height: u64,
+) -> crate::Result<near_primitives::views::AccountView> {
     let request = near_jsonrpc_client::methods::query::RpcQueryRequest {
         block_reference: near_primitives::types::BlockId::Height(block_height).into(),
         request: near_primitives::views::QueryRequest::ViewAccount {
@@ -16,7 +45,7 @@ pub(crate) async fn check_account_exists(
         },
     };
     for _ in 0..5 {
-        match rpc_client.call(&request).await {
+        match rpc_client.call(&request, Some(block_height)).await {
             Err(near_jsonrpc_client::errors::JsonRpcError::ServerError(
                 near_jsonrpc_client::errors::JsonRpcServerError::HandlerError(
                     near_jsonrpc_client::methods::query::RpcQueryError::UnknownAccount { .. },
@@ -30,13 +59,15 @@ pub(crate) async fn check_account_exists(
             }
             Err(err) => {
                 tracing::warn!(target: crate::LOGGER_MSG, "Checking account existence via JSON RPC failed with: {:?}. Re-trying immediatelly", err);
+                deadline::check_retry(std::time::Duration::ZERO)?;
                 continue;
             }
             Ok(response) => {
-                if let near_jsonrpc_primitives::types::query::QueryResponseKind::ViewAccount(_) =
-                    response.kind
+                if let near_jsonrpc_primitives::types::query::QueryResponseKind::ViewAccount(
+                    account_view,
+                ) = response.kind
                 {
-                    return Ok(());
+                    return Ok(account_view);
                 } else {
                     tracing::warn!(target: crate::LOGGER_MSG, "Checking account existence returned invalid response: {:?}. Re-trying immediatelly", response);
                     continue;
@@ -59,22 +90,13 @@ pub(crate) async fn checked_get_pagination_params(
         limit: types::query_params::checked_get_limit(pagination_params.limit)?,
         after_event_index: match pagination_params.after_event_index {
             None => None,
-            Some(index) => {
-                if index.0 < crate::MIN_EVENT_INDEX {
-                    return Err(errors::ErrorKind::InvalidInput(format!(
-                        "after_event_index {} is too low. Please copy event_index value from the last item in your previous response",
-                        index.0
-                    ))
-                        .into());
-                }
-                Some(index.0)
-            }
+            Some(index) => Some(types::EventIndex::checked_from(index.0)?.into()),
         },
     })
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use crate::db_helpers;
 
     pub(crate) async fn init_explorer_db() -> db_helpers::ExplorerPool {
@@ -97,17 +119,20 @@ mod tests {
         ))
     }
 
-    pub(crate) fn init_rpc() -> near_jsonrpc_client::JsonRpcClient {
+    pub(crate) fn init_rpc() -> crate::rpc_pool::RpcPool {
         dotenv::dotenv().ok();
-        let rpc_url = &std::env::var("RPC_URL").expect("failed to get RPC url");
-        let connector = near_jsonrpc_client::JsonRpcClient::new_client();
-        connector.connect(rpc_url)
+        let rpc_url = std::env::var("RPC_URL").expect("failed to get RPC url");
+        crate::rpc_pool::RpcPool::new(&crate::config::RpcConfig {
+            url: rpc_url,
+            ..Default::default()
+        })
     }
 
     pub(crate) fn get_block() -> db_helpers::Block {
         db_helpers::Block {
             timestamp: 1670867692546051383, // December 12, 2022
             height: 80500000,
+            finality: crate::types::query_params::Finality::Final,
         }
     }
 }
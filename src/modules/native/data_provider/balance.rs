@@ -7,7 +7,7 @@ pub(crate) async fn get_near_balance(
     account_id: &near_primitives::types::AccountId,
 ) -> crate::Result<native::schemas::NearBalanceResponse> {
     // +1 because we need to include given timestamp to result. Query has strict less operator
-    let after_event_index = db_helpers::timestamp_to_event_index(block.timestamp + 1);
+    let after_event_index = types::EventIndex::from_timestamp(block.timestamp + 1);
     let balances = db_helpers::select_retry_or_panic::<super::models::Balance>(
         pool_balances,
         r"
@@ -32,6 +32,7 @@ pub(crate) async fn get_near_balance(
             },
             block_timestamp_nanos: block.timestamp.into(),
             block_height: block.height.into(),
+            finality: block.finality,
         }),
         None => Err(errors::ErrorKind::DBError(format!(
             "Could not find the data in near_balance_events table for account_id {}",
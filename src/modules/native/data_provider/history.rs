@@ -1,19 +1,46 @@
 use std::str::FromStr;
 
 use crate::modules::native;
-use crate::{db_helpers, errors, types};
+use crate::{cache, db_helpers, errors, types};
 
+/// The history at the old blocks never changes, so it is cached (see `cache::cached`)
 pub(crate) async fn get_near_history(
+    pool_explorer: &db_helpers::ExplorerPool,
+    pool_balances: &db_helpers::BalancesPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    pagination: &types::query_params::Pagination,
+    with_transaction_info: bool,
+) -> crate::Result<Vec<native::schemas::HistoryItem>> {
+    cache::cached(
+        "get_near_history",
+        format!("{}|{:?}|{}", account_id, pagination, with_transaction_info),
+        block.height,
+        load_near_history(
+            pool_explorer,
+            pool_balances,
+            account_id,
+            block,
+            pagination,
+            with_transaction_info,
+        ),
+    )
+    .await
+}
+
+async fn load_near_history(
+    pool_explorer: &db_helpers::ExplorerPool,
     db_helpers::BalancesPool(pool_balances): &db_helpers::BalancesPool,
     account_id: &near_primitives::types::AccountId,
     block: &db_helpers::Block,
     pagination: &types::query_params::Pagination,
+    with_transaction_info: bool,
 ) -> crate::Result<Vec<native::schemas::HistoryItem>> {
     let after_event_index = if let Some(index) = pagination.after_event_index {
-        index
+        types::EventIndex::new(index)
     } else {
         // +1 because we need to include given timestamp to result. Query has strict less operator
-        db_helpers::timestamp_to_event_index(block.timestamp + 1)
+        types::EventIndex::from_timestamp(block.timestamp + 1)
     };
 
     let query = r"
@@ -25,7 +52,8 @@ pub(crate) async fn get_near_history(
             cause,
             status,
             block_timestamp block_timestamp_nanos,
-            block_height
+            block_height,
+            receipt_id
         FROM near_balance_events
         WHERE affected_account_id = $1 AND event_index < $2::numeric(38, 0)
         ORDER BY event_index DESC
@@ -43,9 +71,23 @@ pub(crate) async fn get_near_history(
     )
     .await?;
 
+    let receipt_transactions = db_helpers::get_receipt_transactions(
+        pool_explorer,
+        history_info
+            .iter()
+            .map(|history| history.receipt_id.clone())
+            .collect(),
+        with_transaction_info,
+    )
+    .await?;
+
     let mut result: Vec<native::schemas::HistoryItem> = vec![];
-    for history in history_info {
-        result.push(history.try_into()?);
+    for (history, receipt_transaction) in history_info.into_iter().zip(receipt_transactions) {
+        let mut item: native::schemas::HistoryItem = history.try_into()?;
+        item.receipt_id = receipt_transaction.receipt_id;
+        item.transaction_hash = receipt_transaction.transaction_hash;
+        item.signer_account_id = receipt_transaction.signer_account_id;
+        result.push(item);
     }
     Ok(result)
 }
@@ -70,6 +112,10 @@ impl TryFrom<super::models::NearHistoryInfo> for native::schemas::HistoryItem {
             metadata: super::get_near_metadata(),
             block_timestamp_nanos: types::numeric::to_u64(&history.block_timestamp_nanos)?.into(),
             block_height: types::numeric::to_u64(&history.block_height)?.into(),
+            event_index_parts: None,
+            receipt_id: None,
+            transaction_hash: None,
+            signer_account_id: None,
         })
     }
 }
@@ -81,6 +127,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_near_history() {
+        let pool_explorer = init_explorer_db().await;
         let pool_balances = init_balances_db().await;
         let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
         let pagination = types::query_params::Pagination {
@@ -89,7 +136,15 @@ mod tests {
         };
         let block = get_block();
 
-        let history = get_near_history(&pool_balances, &account, &block, &pagination).await;
+        let history = get_near_history(
+            &pool_explorer,
+            &pool_balances,
+            &account,
+            &block,
+            &pagination,
+            false,
+        )
+        .await;
         insta::assert_debug_snapshot!(history);
     }
 
@@ -107,7 +162,15 @@ mod tests {
             .await
             .unwrap();
 
-        let history = get_near_history(&pool_balances, &account, &block, &pagination).await;
+        let history = get_near_history(
+            &pool_explorer,
+            &pool_balances,
+            &account,
+            &block,
+            &pagination,
+            false,
+        )
+        .await;
         insta::assert_debug_snapshot!(history);
         assert!(
             history.unwrap().first().unwrap().event_index.0 < index,
@@ -128,12 +191,21 @@ mod tests {
             .await
             .unwrap();
 
-        let history = get_near_history(&pool_balances, &account, &block, &pagination).await;
+        let history = get_near_history(
+            &pool_explorer,
+            &pool_balances,
+            &account,
+            &block,
+            &pagination,
+            false,
+        )
+        .await;
         insta::assert_debug_snapshot!(history);
     }
 
     #[tokio::test]
     async fn test_near_history_account_never_existed() {
+        let pool_explorer = init_explorer_db().await;
         let pool_balances = init_balances_db().await;
         let account =
             near_primitives::types::AccountId::from_str("two-idiots-and-a-half.near").unwrap();
@@ -143,14 +215,22 @@ mod tests {
         };
         let block = get_block();
 
-        let history = get_near_history(&pool_balances, &account, &block, &pagination)
-            .await
-            .unwrap();
+        let history = get_near_history(
+            &pool_explorer,
+            &pool_balances,
+            &account,
+            &block,
+            &pagination,
+            false,
+        )
+        .await
+        .unwrap();
         assert!(history.is_empty());
     }
 
     #[tokio::test]
     async fn test_near_history_account_deleted() {
+        let pool_explorer = init_explorer_db().await;
         let pool_balances = init_balances_db().await;
         let account = near_primitives::types::AccountId::from_str("tezeract.near").unwrap();
         let pagination = types::query_params::Pagination {
@@ -159,10 +239,46 @@ mod tests {
         };
         let block = get_block();
 
-        let history = get_near_history(&pool_balances, &account, &block, &pagination)
-            .await
-            .unwrap();
+        let history = get_near_history(
+            &pool_explorer,
+            &pool_balances,
+            &account,
+            &block,
+            &pagination,
+            false,
+        )
+        .await
+        .unwrap();
         // we still show the history
         insta::assert_debug_snapshot!(history);
     }
+
+    #[tokio::test]
+    async fn test_near_history_with_transaction_info() {
+        let pool_explorer = init_explorer_db().await;
+        let pool_balances = init_balances_db().await;
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 5,
+            after_event_index: None,
+        };
+        let block = get_block();
+
+        let history = get_near_history(
+            &pool_explorer,
+            &pool_balances,
+            &account,
+            &block,
+            &pagination,
+            true,
+        )
+        .await
+        .unwrap();
+        assert!(!history.is_empty());
+        for item in history {
+            assert!(item.receipt_id.is_some());
+            assert!(item.transaction_hash.is_some());
+            assert!(item.signer_account_id.is_some());
+        }
+    }
 }
@@ -15,4 +15,5 @@ pub(crate) struct NearHistoryInfo {
     pub status: String,
     pub block_timestamp_nanos: BigDecimal,
     pub block_height: BigDecimal,
+    pub receipt_id: Option<String>,
 }
@@ -14,12 +14,12 @@ use crate::{db_helpers, modules, types};
 pub async fn get_near_balance(
     pool_balances: web::Data<db_helpers::BalancesPool>,
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::BalanceRequest>,
     block_params: web::Query<types::query_params::BlockParams>,
 ) -> crate::Result<Json<schemas::NearBalanceResponse>> {
-    let block = db_helpers::checked_get_block(&pool_explorer, &block_params).await?;
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
     modules::check_account_exists(&rpc_client, &request.account_id.0, block.height).await?;
 
     Ok(Json(
@@ -33,25 +33,38 @@ pub async fn get_near_balance(
 /// This endpoint returns the history of NEAR operations
 /// for the given `account_id`, `block_timestamp_nanos`/`block_height`.
 /// For the next page, use `event_index` of the last item in your previous response.
+/// Set `with_event_index_parts` to get the decoded `event_index` for each item.
+/// Set `skip_transaction_info` if you don't need the transaction for each item, it makes the response faster.
 pub async fn get_near_history(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
     pool_balances: web::Data<db_helpers::BalancesPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::BalanceRequest>,
     pagination_params: web::Query<types::query_params::PaginationParams>,
+    history_params: web::Query<types::query_params::HistoryParams>,
 ) -> crate::Result<Json<schemas::NearHistoryResponse>> {
     let pagination = modules::checked_get_pagination_params(&pagination_params).await?;
     let block = db_helpers::get_block_from_pagination(&pool_explorer, &pagination).await?;
     // we don't need to check whether account exists. If not, we can just return the empty history
 
+    let mut history = data_provider::get_near_history(
+        &pool_explorer,
+        &pool_balances,
+        &request.account_id,
+        &block,
+        &pagination,
+        !history_params.skip_transaction_info.unwrap_or_default(),
+    )
+    .await?;
+    if history_params.with_event_index_parts.unwrap_or_default() {
+        for item in history.iter_mut() {
+            item.event_index_parts =
+                Some(types::EventIndex::new(item.event_index.0).balance_parts());
+        }
+    }
+
     Ok(Json(schemas::NearHistoryResponse {
-        history: data_provider::get_near_history(
-            &pool_balances,
-            &request.account_id,
-            &block,
-            &pagination,
-        )
-        .await?,
+        history,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
     }))
@@ -30,6 +30,7 @@ pub struct NearBalanceResponse {
     pub balance: NearBalance,
     pub block_timestamp_nanos: types::U64,
     pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
@@ -58,6 +59,12 @@ pub struct HistoryItem {
     pub metadata: Metadata,
     pub block_timestamp_nanos: types::U64,
     pub block_height: types::U64,
+    /// Provided if `with_event_index_parts` is set
+    pub event_index_parts: Option<types::EventIndexParts>,
+    /// Provided unless `skip_transaction_info` is set
+    pub receipt_id: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub signer_account_id: Option<types::AccountId>,
 }
 
 /// This type describes general Metadata info
@@ -0,0 +1,285 @@
+use crate::modules::nft;
+use crate::{db_helpers, errors, rpc_helpers, types};
+
+/// Returns the page of NFTs from the collection and the total supply.
+/// We use NEP-181 enumeration if the contract supports it,
+/// otherwise we collect the tokens from mint/burn events in the DB
+pub(crate) async fn get_nft_collection(
+    pool_explorer: &db_helpers::ExplorerPool,
+    rpc_client: &crate::rpc_pool::RpcPool,
+    contract_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    from_index: u32,
+    limit: u32,
+    media: &super::media::NftMediaContext<'_>,
+) -> crate::Result<(Vec<nft::schemas::Nft>, u128)> {
+    // https://nomicon.io/Standards/Tokens/NonFungibleToken/Enumeration
+    let request = rpc_helpers::get_function_call_request(
+        block.height,
+        contract_id.clone(),
+        "nft_tokens",
+        serde_json::json!({ "from_index": from_index.to_string(), "limit": limit }),
+    );
+    match rpc_helpers::call_function(rpc_client, request, block.height, contract_id).await? {
+        rpc_helpers::FunctionCallResult::Success(response) => {
+            get_nft_collection_from_contract(rpc_client, contract_id, block, response, media).await
+        }
+        rpc_helpers::FunctionCallResult::NotImplemented => {
+            get_nft_collection_from_events(
+                pool_explorer,
+                rpc_client,
+                contract_id,
+                block,
+                from_index,
+                limit,
+                media,
+            )
+            .await
+        }
+        rpc_helpers::FunctionCallResult::Failed(vm_error) => {
+            Err(errors::ErrorKind::ContractError(vm_error).into())
+        }
+    }
+}
+
+async fn get_nft_collection_from_contract(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    contract_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    nft_tokens_response: near_primitives::views::CallResult,
+    media: &super::media::NftMediaContext<'_>,
+) -> crate::Result<(Vec<nft::schemas::Nft>, u128)> {
+    let tokens =
+        serde_json::from_slice::<Vec<super::nft_info::Token>>(&nft_tokens_response.result)?;
+    let mut nfts = vec![];
+    for token in tokens {
+        nfts.push(super::nft_info::to_nft(token, block.timestamp, media)?);
+    }
+
+    let request = rpc_helpers::get_function_call_request(
+        block.height,
+        contract_id.clone(),
+        "nft_total_supply",
+        serde_json::json!({}),
+    );
+    let response =
+        rpc_helpers::wrapped_call(rpc_client, request, block.height, contract_id).await?;
+    let total_supply = serde_json::from_slice::<types::U128>(&response.result)?.0;
+
+    Ok((nfts, total_supply))
+}
+
+async fn get_nft_collection_from_events(
+    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    rpc_client: &crate::rpc_pool::RpcPool,
+    contract_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    from_index: u32,
+    limit: u32,
+    media: &super::media::NftMediaContext<'_>,
+) -> crate::Result<(Vec<nft::schemas::Nft>, u128)> {
+    // The last successful event for each token shows whether it still exists and who is the owner
+    let query = r"
+        WITH last_token_events AS (
+            SELECT DISTINCT ON (token_id) token_id, event_kind, token_new_owner_account_id
+            FROM assets__non_fungible_token_events
+                JOIN execution_outcomes ON assets__non_fungible_token_events.emitted_for_receipt_id = execution_outcomes.receipt_id
+            WHERE emitted_by_contract_account_id = $1
+                AND emitted_at_block_timestamp <= $2::numeric(20, 0)
+                AND execution_outcomes.status IN ('SUCCESS_VALUE', 'SUCCESS_RECEIPT_ID')
+            ORDER BY token_id,
+                emitted_at_block_timestamp DESC,
+                emitted_in_shard_id DESC,
+                emitted_index_of_event_entry_in_shard DESC
+        ),
+        tokens AS (
+            SELECT token_id, token_new_owner_account_id owner_account_id
+            FROM last_token_events
+            WHERE event_kind::text != 'BURN'
+        )
+        SELECT totals.total_supply, page.token_id, page.owner_account_id
+        FROM (SELECT count(*) total_supply FROM tokens) totals
+            LEFT JOIN (
+                SELECT token_id, owner_account_id
+                FROM tokens
+                ORDER BY token_id
+                OFFSET $3::bigint
+                LIMIT $4::bigint
+            ) page ON true
+        ORDER BY page.token_id
+    ";
+    let tokens_info = db_helpers::select_retry_or_panic::<super::models::NftTokenInfo>(
+        pool_explorer,
+        query,
+        &[
+            contract_id.to_string(),
+            block.timestamp.to_string(),
+            from_index.to_string(),
+            limit.to_string(),
+        ],
+    )
+    .await?;
+
+    let total_supply = tokens_info
+        .first()
+        .map(|info| info.total_supply as u128)
+        .unwrap_or_default();
+    // The tokens are independent, there is no need to wait for each of them
+    let nfts = futures::future::try_join_all(tokens_info.into_iter().filter_map(|info| {
+        match (info.token_id, info.owner_account_id) {
+            (Some(token_id), Some(owner_account_id)) => Some(async move {
+                // The contract could have no `nft_token` method as well, we still can show the owner
+                let nft = super::nft_info::get_implemented_nft(
+                    rpc_client,
+                    contract_id.clone(),
+                    token_id.clone(),
+                    block,
+                    false,
+                    media,
+                )
+                .await?
+                .unwrap_or_else(|| {
+                    let metadata = get_empty_nft_metadata();
+                    nft::schemas::Nft {
+                        token_id,
+                        owner_account_id,
+                        validity: super::nft_info::get_nft_validity(&metadata, block.timestamp),
+                        metadata,
+                        media_url: None,
+                        media_hash_status: None,
+                        approvals: None,
+                        royalties: None,
+                        resolved_reference: None,
+                    }
+                });
+                Ok::<nft::schemas::Nft, crate::errors::Error>(nft)
+            }),
+            _ => None,
+        }
+    }))
+    .await?;
+    Ok((nfts, total_supply))
+}
+
+fn get_empty_nft_metadata() -> nft::schemas::NftMetadata {
+    nft::schemas::NftMetadata {
+        title: None,
+        description: None,
+        media: None,
+        media_hash: None,
+        copies: None,
+        issued_at: None,
+        expires_at: None,
+        starts_at: None,
+        updated_at: None,
+        extra: None,
+        reference: None,
+        reference_hash: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::modules::tests::*;
+    use std::str::FromStr;
+
+    fn get_media(gateways: &config::NftGatewaysConfig) -> super::super::media::NftMediaContext<'_> {
+        super::super::media::NftMediaContext {
+            base_uri: None,
+            gateways,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nft_collection() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let gateways = config::NftGatewaysConfig::default();
+
+        let (nfts, total_supply) = get_nft_collection(
+            &pool_explorer,
+            &rpc_client,
+            &contract,
+            &block,
+            0,
+            5,
+            &get_media(&gateways),
+        )
+        .await
+        .unwrap();
+        assert_eq!(nfts.len(), 5);
+        assert!(total_supply >= 5);
+    }
+
+    #[tokio::test]
+    async fn test_nft_collection_next_page() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let gateways = config::NftGatewaysConfig::default();
+        let media = get_media(&gateways);
+
+        let (page1, _) =
+            get_nft_collection(&pool_explorer, &rpc_client, &contract, &block, 0, 5, &media)
+                .await
+                .unwrap();
+        let (page2, _) =
+            get_nft_collection(&pool_explorer, &rpc_client, &contract, &block, 5, 5, &media)
+                .await
+                .unwrap();
+        assert!(page2
+            .iter()
+            .all(|nft| page1.iter().all(|prev| prev.token_id != nft.token_id)));
+    }
+
+    #[tokio::test]
+    async fn test_nft_collection_from_events() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let gateways = config::NftGatewaysConfig::default();
+
+        let (nfts, total_supply) = get_nft_collection_from_events(
+            &pool_explorer,
+            &rpc_client,
+            &contract,
+            &block,
+            0,
+            5,
+            &get_media(&gateways),
+        )
+        .await
+        .unwrap();
+        assert_eq!(nfts.len(), 5);
+        assert!(total_supply >= 5);
+    }
+
+    #[tokio::test]
+    async fn test_nft_collection_no_contract_deployed() {
+        let pool_explorer = init_explorer_db().await;
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("olga.near").unwrap();
+        let gateways = config::NftGatewaysConfig::default();
+
+        let (nfts, total_supply) = get_nft_collection(
+            &pool_explorer,
+            &rpc_client,
+            &contract,
+            &block,
+            0,
+            5,
+            &get_media(&gateways),
+        )
+        .await
+        .unwrap();
+        assert!(nfts.is_empty());
+        assert_eq!(total_supply, 0);
+    }
+}
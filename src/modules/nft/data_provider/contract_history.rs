@@ -0,0 +1,306 @@
+use crate::modules::nft;
+use crate::{cache, db_helpers, errors, types};
+
+const NFT_EVENT_KINDS: [&str; 3] = ["MINT", "TRANSFER", "BURN"];
+
+/// The history at the old blocks never changes, so it is cached (see `cache::cached`)
+pub(crate) async fn get_nft_contract_history(
+    pool_explorer: &db_helpers::ExplorerPool,
+    contract_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    pagination: &types::query_params::Pagination,
+    filter: &nft::schemas::NftHistoryFilterParams,
+    with_transaction_info: bool,
+) -> crate::Result<Vec<nft::schemas::ContractHistoryItem>> {
+    cache::cached(
+        "get_nft_contract_history",
+        format!(
+            "{}|{:?}|{:?}|{}",
+            contract_id, pagination, filter, with_transaction_info
+        ),
+        block.height,
+        load_nft_contract_history(
+            pool_explorer,
+            contract_id,
+            block,
+            pagination,
+            filter,
+            with_transaction_info,
+        ),
+    )
+    .await
+}
+
+async fn load_nft_contract_history(
+    pool_explorer: &db_helpers::ExplorerPool,
+    contract_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    pagination: &types::query_params::Pagination,
+    filter: &nft::schemas::NftHistoryFilterParams,
+    with_transaction_info: bool,
+) -> crate::Result<Vec<nft::schemas::ContractHistoryItem>> {
+    let cause = match &filter.cause {
+        Some(cause) => {
+            let cause = cause.to_uppercase();
+            if !NFT_EVENT_KINDS.contains(&cause.as_str()) {
+                return Err(errors::ErrorKind::InvalidInput(format!(
+                    "cause should be one of {:?}",
+                    NFT_EVENT_KINDS
+                ))
+                .into());
+            }
+            cause
+        }
+        None => "".to_string(),
+    };
+    let account_id = match &filter.account_id {
+        Some(account_id) => account_id.to_string(),
+        None => "".to_string(),
+    };
+    let (after_timestamp, after_shard_id, after_index_in_shard) =
+        if let Some(index) = pagination.after_event_index {
+            let parts = types::EventIndex::new(index).shard_event_parts();
+            (
+                parts.block_timestamp_nanos.0,
+                parts.chunk_index,
+                parts.index_in_chunk,
+            )
+        } else {
+            // +1 because we need to include given timestamp to result. Query has strict less operator
+            (block.timestamp + 1, 0, 0)
+        };
+
+    let query = r"
+        SELECT
+            token_id,
+            event_kind::text cause,
+            CASE WHEN execution_outcomes.status IN ('SUCCESS_VALUE', 'SUCCESS_RECEIPT_ID') THEN 'SUCCESS'
+                ELSE 'FAILURE'
+            END status,
+            token_old_owner_account_id old_account_id,
+            token_new_owner_account_id new_account_id,
+            emitted_in_shard_id shard_id,
+            emitted_index_of_event_entry_in_shard index_in_shard,
+            emitted_at_block_timestamp block_timestamp_nanos,
+            block_height,
+            emitted_for_receipt_id receipt_id
+        FROM assets__non_fungible_token_events
+            JOIN blocks ON assets__non_fungible_token_events.emitted_at_block_timestamp = blocks.block_timestamp
+            JOIN execution_outcomes ON assets__non_fungible_token_events.emitted_for_receipt_id = execution_outcomes.receipt_id
+        WHERE emitted_by_contract_account_id = $1
+            AND (emitted_at_block_timestamp, emitted_in_shard_id, emitted_index_of_event_entry_in_shard)
+                < ($2::numeric(20, 0), $3::numeric(20, 0), $4::integer)
+            AND ($5 = '' OR event_kind::text = $5)
+            AND ($6 = '' OR token_old_owner_account_id = $6 OR token_new_owner_account_id = $6)
+        ORDER BY emitted_at_block_timestamp DESC,
+            emitted_in_shard_id DESC,
+            emitted_index_of_event_entry_in_shard DESC
+        LIMIT $7::numeric(20, 0)
+    ";
+    let history_items = db_helpers::select_retry_or_panic::<super::models::NftContractHistoryInfo>(
+        &pool_explorer.0,
+        query,
+        &[
+            contract_id.to_string(),
+            after_timestamp.to_string(),
+            after_shard_id.to_string(),
+            after_index_in_shard.to_string(),
+            cause,
+            account_id,
+            pagination.limit.to_string(),
+        ],
+    )
+    .await?;
+
+    let receipt_transactions = db_helpers::get_receipt_transactions(
+        pool_explorer,
+        history_items
+            .iter()
+            .map(|history| Some(history.receipt_id.clone()))
+            .collect(),
+        with_transaction_info,
+    )
+    .await?;
+
+    let mut result: Vec<nft::schemas::ContractHistoryItem> = vec![];
+    for (history, receipt_transaction) in history_items.into_iter().zip(receipt_transactions) {
+        let mut item: nft::schemas::ContractHistoryItem = history.try_into()?;
+        item.receipt_id = receipt_transaction.receipt_id;
+        item.transaction_hash = receipt_transaction.transaction_hash;
+        item.signer_account_id = receipt_transaction.signer_account_id;
+        result.push(item);
+    }
+    Ok(result)
+}
+
+impl TryFrom<super::models::NftContractHistoryInfo> for nft::schemas::ContractHistoryItem {
+    type Error = errors::Error;
+
+    fn try_from(info: super::models::NftContractHistoryInfo) -> crate::Result<Self> {
+        let block_timestamp_nanos = types::numeric::to_u64(&info.block_timestamp_nanos)?;
+        // assets__non_fungible_token_events does not have event_index column yet (see DB_DESIGN.md),
+        // so we build it from the event position in the shard
+        let event_index = types::EventIndex::from_shard_event_parts(
+            block_timestamp_nanos,
+            types::numeric::to_u64(&info.shard_id)? as u32,
+            u32::try_from(info.index_in_shard).map_err(|_| {
+                errors::ErrorKind::InternalError(format!(
+                    "Could not encode event_index for the event {} in receipt {}",
+                    info.index_in_shard, info.receipt_id
+                ))
+            })?,
+        )?;
+        Ok(Self {
+            event_index: u128::from(event_index).into(),
+            token_id: info.token_id,
+            cause: info.cause,
+            old_account_id: types::account_id::extract_account_id(&info.old_account_id)?
+                .map(|account| account.into()),
+            new_account_id: types::account_id::extract_account_id(&info.new_account_id)?
+                .map(|account| account.into()),
+            status: info.status,
+            block_timestamp_nanos: block_timestamp_nanos.into(),
+            block_height: types::numeric::to_u64(&info.block_height)?.into(),
+            event_index_parts: None,
+            receipt_id: None,
+            transaction_hash: None,
+            signer_account_id: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+    use std::str::FromStr;
+
+    fn no_filter() -> nft::schemas::NftHistoryFilterParams {
+        nft::schemas::NftHistoryFilterParams {
+            cause: None,
+            account_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nft_contract_history() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 10,
+            after_event_index: None,
+        };
+
+        let history = get_nft_contract_history(
+            &pool_explorer,
+            &contract,
+            &block,
+            &pagination,
+            &no_filter(),
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(history.len(), 10);
+        for item in &history {
+            assert!(item.block_timestamp_nanos.0 <= block.timestamp);
+            assert!(item.receipt_id.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nft_contract_history_next_page() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 5,
+            after_event_index: None,
+        };
+        let page1 = get_nft_contract_history(
+            &pool_explorer,
+            &contract,
+            &block,
+            &pagination,
+            &no_filter(),
+            false,
+        )
+        .await
+        .unwrap();
+
+        let pagination = types::query_params::Pagination {
+            limit: 5,
+            after_event_index: Some(page1.last().unwrap().event_index.0),
+        };
+        let page2 = get_nft_contract_history(
+            &pool_explorer,
+            &contract,
+            &block,
+            &pagination,
+            &no_filter(),
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert!(
+            page1.last().unwrap().event_index > page2.first().unwrap().event_index,
+            "Next page should not include events from previous page"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nft_contract_history_filtered() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 10,
+            after_event_index: None,
+        };
+        let filter = nft::schemas::NftHistoryFilterParams {
+            cause: Some("mint".to_string()),
+            account_id: None,
+        };
+
+        let history = get_nft_contract_history(
+            &pool_explorer,
+            &contract,
+            &block,
+            &pagination,
+            &filter,
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(!history.is_empty());
+        assert!(history.iter().all(|item| item.cause == "MINT"));
+    }
+
+    #[tokio::test]
+    async fn test_nft_contract_history_invalid_cause() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 10,
+            after_event_index: None,
+        };
+        let filter = nft::schemas::NftHistoryFilterParams {
+            cause: Some("approve".to_string()),
+            account_id: None,
+        };
+
+        let history = get_nft_contract_history(
+            &pool_explorer,
+            &contract,
+            &block,
+            &pagination,
+            &filter,
+            false,
+        )
+        .await;
+        assert!(history.is_err());
+    }
+}
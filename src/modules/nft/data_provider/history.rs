@@ -3,11 +3,12 @@ use crate::{db_helpers, errors, types};
 
 // TODO PHASE 2 pagination by artificial index added to assets__non_fungible_token_events
 pub(crate) async fn get_nft_history(
-    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    pool_explorer: &db_helpers::ExplorerPool,
     contract_id: &near_primitives::types::AccountId,
     token_id: &str,
     block: &db_helpers::Block,
     limit: u32,
+    with_transaction_info: bool,
 ) -> crate::Result<Vec<nft::schemas::HistoryItem>> {
     let query = r"
         SELECT
@@ -18,7 +19,8 @@ pub(crate) async fn get_nft_history(
             token_old_owner_account_id old_account_id,
             token_new_owner_account_id new_account_id,
             emitted_at_block_timestamp block_timestamp_nanos,
-            block_height
+            block_height,
+            emitted_for_receipt_id receipt_id
         FROM assets__non_fungible_token_events
             JOIN blocks ON assets__non_fungible_token_events.emitted_at_block_timestamp = blocks.block_timestamp
             JOIN execution_outcomes ON assets__non_fungible_token_events.emitted_for_receipt_id = execution_outcomes.receipt_id
@@ -29,7 +31,7 @@ pub(crate) async fn get_nft_history(
         LIMIT $4::numeric(20, 0)
     ";
     let history_items = db_helpers::select_retry_or_panic::<super::models::NftHistoryInfo>(
-        pool_explorer,
+        &pool_explorer.0,
         query,
         &[
             token_id.to_string(),
@@ -40,9 +42,23 @@ pub(crate) async fn get_nft_history(
     )
     .await?;
 
+    let receipt_transactions = db_helpers::get_receipt_transactions(
+        pool_explorer,
+        history_items
+            .iter()
+            .map(|history| Some(history.receipt_id.clone()))
+            .collect(),
+        with_transaction_info,
+    )
+    .await?;
+
     let mut result: Vec<nft::schemas::HistoryItem> = vec![];
-    for history in history_items {
-        result.push(history.try_into()?);
+    for (history, receipt_transaction) in history_items.into_iter().zip(receipt_transactions) {
+        let mut item: nft::schemas::HistoryItem = history.try_into()?;
+        item.receipt_id = receipt_transaction.receipt_id;
+        item.transaction_hash = receipt_transaction.transaction_hash;
+        item.signer_account_id = receipt_transaction.signer_account_id;
+        result.push(item);
     }
     Ok(result)
 }
@@ -60,6 +76,9 @@ impl TryFrom<super::models::NftHistoryInfo> for nft::schemas::HistoryItem {
                 .map(|account| account.into()),
             block_timestamp_nanos: types::numeric::to_u64(&info.block_timestamp_nanos)?.into(),
             block_height: types::numeric::to_u64(&info.block_height)?.into(),
+            receipt_id: None,
+            transaction_hash: None,
+            signer_account_id: None,
         })
     }
 }
@@ -77,7 +96,7 @@ mod tests {
         let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
         let token = "293708:1";
 
-        let history = get_nft_history(&pool_explorer, &contract, token, &block, 10).await;
+        let history = get_nft_history(&pool_explorer, &contract, token, &block, 10, false).await;
         insta::assert_debug_snapshot!(history);
     }
 
@@ -88,7 +107,7 @@ mod tests {
         let contract = near_primitives::types::AccountId::from_str("thebullishbulls.near").unwrap();
         let token = "1349";
 
-        let history = get_nft_history(&pool_explorer, &contract, token, &block, 10).await;
+        let history = get_nft_history(&pool_explorer, &contract, token, &block, 10, false).await;
         insta::assert_debug_snapshot!(history);
     }
 
@@ -99,9 +118,27 @@ mod tests {
         let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
         let token = "no_such_token";
 
-        let history = get_nft_history(&pool_explorer, &contract, token, &block, 10)
+        let history = get_nft_history(&pool_explorer, &contract, token, &block, 10, false)
             .await
             .unwrap();
         assert!(history.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_nft_history_with_transaction_info() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let token = "293708:1";
+
+        let history = get_nft_history(&pool_explorer, &contract, token, &block, 10, true)
+            .await
+            .unwrap();
+        assert!(!history.is_empty());
+        for item in history {
+            assert!(item.receipt_id.is_some());
+            assert!(item.transaction_hash.is_some());
+            assert!(item.signer_account_id.is_some());
+        }
+    }
 }
@@ -0,0 +1,191 @@
+use std::str::FromStr;
+
+use crate::modules::nft;
+use crate::{cache, db_helpers, errors, types};
+
+/// Returns the page of NFT holders sorted by the number of tokens they hold,
+/// and the total number of unique holders at the given block.
+/// The result at the old blocks never changes, so it is cached (see `cache::cached`)
+pub(crate) async fn get_nft_holders(
+    pool_explorer: &db_helpers::ExplorerPool,
+    contract_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    limit: u32,
+    after_account_id: Option<&types::AccountId>,
+) -> crate::Result<(Vec<nft::schemas::NftHolder>, u64)> {
+    cache::cached(
+        "get_nft_holders",
+        format!("{}|{}|{:?}", contract_id, limit, after_account_id),
+        block.height,
+        load_nft_holders(pool_explorer, contract_id, block, limit, after_account_id),
+    )
+    .await
+}
+
+async fn load_nft_holders(
+    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    contract_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    limit: u32,
+    after_account_id: Option<&types::AccountId>,
+) -> crate::Result<(Vec<nft::schemas::NftHolder>, u64)> {
+    // The same counting as in `get_nfts_count`, but grouped by the owner instead of the contract
+    let query = r"
+        WITH relevant_events AS (
+            SELECT token_old_owner_account_id, token_new_owner_account_id
+            FROM assets__non_fungible_token_events
+                JOIN execution_outcomes ON assets__non_fungible_token_events.emitted_for_receipt_id = execution_outcomes.receipt_id
+            WHERE emitted_by_contract_account_id = $1
+                AND emitted_at_block_timestamp <= $2::numeric(20, 0)
+                AND execution_outcomes.status IN ('SUCCESS_VALUE', 'SUCCESS_RECEIPT_ID')
+        ),
+        outgoing_events_count AS (
+            SELECT token_old_owner_account_id account_id, count(*) * -1 cnt FROM relevant_events
+            -- mint events do not have the old owner
+            WHERE token_old_owner_account_id != ''
+            GROUP BY token_old_owner_account_id
+        ),
+        ingoing_events_count AS (
+            SELECT token_new_owner_account_id account_id, count(*) cnt FROM relevant_events
+            -- burn events do not have the new owner
+            WHERE token_new_owner_account_id != ''
+            GROUP BY token_new_owner_account_id
+        ),
+        holders AS (
+            SELECT coalesce(ingoing_events_count.account_id, outgoing_events_count.account_id) account_id,
+                -- coalesce changes null to the given parameter
+                coalesce(ingoing_events_count.cnt, 0) + coalesce(outgoing_events_count.cnt, 0) cnt
+            FROM ingoing_events_count FULL JOIN outgoing_events_count
+                ON ingoing_events_count.account_id = outgoing_events_count.account_id
+        ),
+        cursor_holder AS (
+            SELECT cnt FROM holders WHERE account_id = $3
+        )
+        SELECT totals.total_holders, totals.cursor_found, page.account_id, page.nft_count
+        FROM (
+            SELECT count(*) total_holders,
+                EXISTS (SELECT 1 FROM cursor_holder WHERE cnt > 0) cursor_found
+            FROM holders WHERE cnt > 0
+        ) totals
+            LEFT JOIN (
+                SELECT account_id, cnt nft_count
+                FROM holders
+                WHERE cnt > 0
+                    AND ($3 = ''
+                        OR cnt < (SELECT cnt FROM cursor_holder)
+                        OR (cnt = (SELECT cnt FROM cursor_holder) AND account_id > $3))
+                ORDER BY cnt DESC, account_id
+                LIMIT $4::numeric(20, 0)
+            ) page ON true
+        ORDER BY page.nft_count DESC, page.account_id
+    ";
+
+    let holders_info = db_helpers::select_retry_or_panic::<super::models::NftHolderInfo>(
+        pool_explorer,
+        query,
+        &[
+            contract_id.to_string(),
+            block.timestamp.to_string(),
+            after_account_id
+                .map(|account_id| account_id.to_string())
+                .unwrap_or_default(),
+            limit.to_string(),
+        ],
+    )
+    .await?;
+
+    if let Some(after_account_id) = after_account_id {
+        if !holders_info.first().map_or(false, |info| info.cursor_found) {
+            return Err(errors::ErrorKind::InvalidInput(format!(
+                "{} does not hold any NFT of {} at block {}. Please copy account_id value from the last holder in your previous response together with block_height",
+                after_account_id, contract_id, block.height
+            ))
+            .into());
+        }
+    }
+
+    let total_holders = holders_info
+        .first()
+        .map(|info| info.total_holders as u64)
+        .unwrap_or_default();
+    let mut holders: Vec<nft::schemas::NftHolder> = vec![];
+    for info in holders_info {
+        if let (Some(account_id), Some(nft_count)) = (info.account_id, info.nft_count) {
+            holders.push(nft::schemas::NftHolder {
+                account_id: types::AccountId::from_str(&account_id)?,
+                nft_count: nft_count as u32,
+            });
+        }
+    }
+    Ok((holders, total_holders))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+
+    #[tokio::test]
+    async fn test_nft_holders() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+
+        let (holders, total_holders) = get_nft_holders(&pool_explorer, &contract, &block, 10, None)
+            .await
+            .unwrap();
+        assert_eq!(holders.len(), 10);
+        assert!(total_holders >= 10);
+        for pair in holders.windows(2) {
+            assert!(pair[0].nft_count >= pair[1].nft_count);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nft_holders_next_page() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+
+        let (page1, total_holders1) = get_nft_holders(&pool_explorer, &contract, &block, 5, None)
+            .await
+            .unwrap();
+        let last = page1.last().unwrap();
+        let (page2, total_holders2) =
+            get_nft_holders(&pool_explorer, &contract, &block, 5, Some(&last.account_id))
+                .await
+                .unwrap();
+
+        assert_eq!(total_holders1, total_holders2);
+        let first = page2.first().unwrap();
+        assert!(
+            first.nft_count < last.nft_count
+                || (first.nft_count == last.nft_count && first.account_id > last.account_id),
+            "Next page should not include holders from previous page"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nft_holders_contract_without_events() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("olga.near").unwrap();
+
+        let (holders, total_holders) = get_nft_holders(&pool_explorer, &contract, &block, 10, None)
+            .await
+            .unwrap();
+        assert!(holders.is_empty());
+        assert_eq!(total_holders, 0);
+    }
+
+    #[tokio::test]
+    async fn test_nft_holders_unknown_cursor() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let cursor = types::AccountId::from_str("not-a-holder.near").unwrap();
+
+        let holders = get_nft_holders(&pool_explorer, &contract, &block, 10, Some(&cursor)).await;
+        assert_eq!(holders.unwrap_err().code, 400);
+    }
+}
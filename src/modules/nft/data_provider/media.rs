@@ -0,0 +1,307 @@
+use crate::config;
+use crate::modules::nft;
+
+const MEDIA_HASH_STATUS_VERIFIED: &str = "VERIFIED";
+const MEDIA_HASH_STATUS_MISMATCH: &str = "MISMATCH";
+// We do not load the media, so we can check only the content-addressed links
+const MEDIA_HASH_STATUS_UNVERIFIED: &str = "UNVERIFIED";
+const MEDIA_HASH_STATUS_NOT_PROVIDED: &str = "NOT_PROVIDED";
+
+// https://github.com/multiformats/multicodec/blob/master/table.csv
+const CID_VERSION_1: u64 = 1;
+const CODEC_RAW: u64 = 0x55;
+const MULTIHASH_SHA2_256: u64 = 0x12;
+const SHA2_256_LENGTH: u64 = 32;
+
+/// The contract-level settings we need to build the absolute media URLs of its NFTs
+pub(crate) struct NftMediaContext<'a> {
+    pub base_uri: Option<&'a str>,
+    pub gateways: &'a config::NftGatewaysConfig,
+}
+
+/// Fills `media_url` and `media_hash_status` for the NFT
+pub(super) fn resolve_nft_media(
+    nft: &mut nft::schemas::Nft,
+    base_uri: Option<&str>,
+    gateways: &config::NftGatewaysConfig,
+) {
+    let media = match nft.metadata.media.as_deref() {
+        Some(media) => media,
+        None => return,
+    };
+    nft.media_url = get_absolute_url(media, base_uri, gateways);
+    nft.media_hash_status = Some(
+        match &nft.metadata.media_hash {
+            None => MEDIA_HASH_STATUS_NOT_PROVIDED,
+            Some(media_hash) => match (get_raw_cid_digest(media), base64::decode(media_hash)) {
+                (Some(digest), Ok(media_hash)) if digest == media_hash => {
+                    MEDIA_HASH_STATUS_VERIFIED
+                }
+                (Some(_), _) => MEDIA_HASH_STATUS_MISMATCH,
+                (None, _) => MEDIA_HASH_STATUS_UNVERIFIED,
+            },
+        }
+        .to_string(),
+    );
+}
+
+/// Converts the link from NFT metadata to the absolute HTTP(S) URL.
+/// Contracts use full URLs, `ipfs://`, `ar://` links, bare IPFS CIDs,
+/// and the paths relative to `base_uri` of the contract.
+/// Returns None if we can't build the URL
+pub(crate) fn get_absolute_url(
+    value: &str,
+    base_uri: Option<&str>,
+    gateways: &config::NftGatewaysConfig,
+) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    if value.starts_with("https://") || value.starts_with("http://") || value.starts_with("data:") {
+        return Some(value.to_string());
+    }
+    if let Some(path) = value.strip_prefix("ipfs://") {
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        return Some(join_url(&gateways.ipfs_gateway, path));
+    }
+    if let Some(path) = value.strip_prefix("/ipfs/") {
+        return Some(join_url(&gateways.ipfs_gateway, path));
+    }
+    if let Some(path) = value.strip_prefix("ar://") {
+        return Some(join_url(&gateways.arweave_gateway, path));
+    }
+    if is_ipfs_cid(value.split('/').next().unwrap_or_default()) {
+        return Some(join_url(&gateways.ipfs_gateway, value));
+    }
+    if value.contains("://") {
+        return None;
+    }
+
+    // base_uri is often the IPFS link itself, so we normalize it the same way
+    let base_uri = get_absolute_url(base_uri?, None, gateways)?;
+    Some(join_url(&base_uri, value))
+}
+
+fn join_url(base: &str, path: &str) -> String {
+    format!(
+        "{}/{}",
+        base.trim().trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+// CIDv0 is base58 sha256 multihash, CIDv1 is usually base32 with `b` multibase prefix
+fn is_ipfs_cid(value: &str) -> bool {
+    (value.len() == 46
+        && value.starts_with("Qm")
+        && value.chars().all(|c| c.is_ascii_alphanumeric()))
+        || (value.len() >= 50
+            && value.starts_with("baf")
+            && value
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c)))
+}
+
+/// If the media is CIDv1 of the raw content, the CID contains sha256 of the content,
+/// which should be equal to `media_hash`
+fn get_raw_cid_digest(media: &str) -> Option<Vec<u8>> {
+    let media = media.trim();
+    let cid = media
+        .strip_prefix("ipfs://")
+        .or_else(|| media.strip_prefix("/ipfs/"))
+        .unwrap_or(media);
+    // The hash of the directory does not say anything about the file inside it
+    if cid.contains('/') || !is_ipfs_cid(cid) {
+        return None;
+    }
+    let bytes = decode_base32(cid.strip_prefix('b')?)?;
+    let mut bytes = bytes.as_slice();
+    if read_varint(&mut bytes)? != CID_VERSION_1
+        || read_varint(&mut bytes)? != CODEC_RAW
+        || read_varint(&mut bytes)? != MULTIHASH_SHA2_256
+        || read_varint(&mut bytes)? != SHA2_256_LENGTH
+        || bytes.len() as u64 != SHA2_256_LENGTH
+    {
+        return None;
+    }
+    Some(bytes.to_vec())
+}
+
+// https://github.com/multiformats/unsigned-varint
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut result: u64 = 0;
+    for (i, byte) in bytes.iter().enumerate().take(9) {
+        result |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            *bytes = &bytes[i + 1..];
+            return Some(result);
+        }
+    }
+    None
+}
+
+// RFC 4648 base32, lowercase without padding, as used by multibase `b` prefix
+fn decode_base32(value: &str) -> Option<Vec<u8>> {
+    let mut result = vec![];
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in value.chars() {
+        let index = match c {
+            'a'..='z' => c as u32 - 'a' as u32,
+            '2'..='7' => c as u32 - '2' as u32 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | index;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // CIDv1 (raw, sha256) of the empty file
+    const EMPTY_FILE_CID: &str = "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku";
+    const EMPTY_FILE_SHA256: &str = "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=";
+
+    fn get_gateways() -> config::NftGatewaysConfig {
+        config::NftGatewaysConfig {
+            ipfs_gateway: "https://gateway.example/ipfs/".to_string(),
+            arweave_gateway: "https://arweave.example".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_absolute_url() {
+        let gateways = get_gateways();
+        let cid = "bafkreidjom4f76uepf7rtanl7psx36ocgtp2ks3xir2vaemtyhzs364wji";
+        let ipfs_url = format!("https://gateway.example/ipfs/{}", cid);
+        for value in [
+            cid.to_string(),
+            format!("ipfs://{}", cid),
+            format!("ipfs://ipfs/{}", cid),
+            format!("/ipfs/{}", cid),
+        ] {
+            assert_eq!(
+                get_absolute_url(&value, None, &gateways),
+                Some(ipfs_url.clone())
+            );
+        }
+        assert_eq!(
+            get_absolute_url(
+                "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/1.png",
+                None,
+                &gateways
+            ),
+            Some(
+                "https://gateway.example/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/1.png"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            get_absolute_url("ar://abc", None, &gateways),
+            Some("https://arweave.example/abc".to_string())
+        );
+        assert_eq!(
+            get_absolute_url(
+                "https://example.com/1.png",
+                Some("https://other.com"),
+                &gateways
+            ),
+            Some("https://example.com/1.png".to_string())
+        );
+    }
+
+    #[test]
+    fn test_absolute_url_relative() {
+        let gateways = get_gateways();
+        assert_eq!(
+            get_absolute_url("1394.png", Some("https://example.com/bulls/"), &gateways),
+            Some("https://example.com/bulls/1394.png".to_string())
+        );
+        assert_eq!(
+            get_absolute_url(
+                "/1394.png",
+                Some("ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"),
+                &gateways
+            ),
+            Some(
+                "https://gateway.example/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/1394.png"
+                    .to_string()
+            )
+        );
+        assert_eq!(get_absolute_url("1394.png", None, &gateways), None);
+        assert_eq!(
+            get_absolute_url("ftp://example.com/1.png", None, &gateways),
+            None
+        );
+        assert_eq!(get_absolute_url(" ", None, &gateways), None);
+    }
+
+    #[test]
+    fn test_raw_cid_digest() {
+        assert_eq!(
+            get_raw_cid_digest(EMPTY_FILE_CID),
+            Some(base64::decode(EMPTY_FILE_SHA256).unwrap())
+        );
+        assert_eq!(
+            get_raw_cid_digest(&format!("ipfs://{}", EMPTY_FILE_CID)),
+            Some(base64::decode(EMPTY_FILE_SHA256).unwrap())
+        );
+        // dag-pb CIDs do not contain the hash of the content
+        assert_eq!(
+            get_raw_cid_digest("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"),
+            None
+        );
+        assert_eq!(get_raw_cid_digest("https://example.com/1.png"), None);
+    }
+
+    #[test]
+    fn test_media_hash_status() {
+        let gateways = get_gateways();
+        let mut nft: nft::schemas::Nft = serde_json::from_value(serde_json::json!({
+            "token_id": "1",
+            "owner_account_id": "olga.near",
+            "metadata": { "media": EMPTY_FILE_CID, "media_hash": EMPTY_FILE_SHA256 },
+            "validity": { "is_valid": true },
+        }))
+        .unwrap();
+        resolve_nft_media(&mut nft, None, &gateways);
+        assert_eq!(
+            nft.media_url,
+            Some(format!("https://gateway.example/ipfs/{}", EMPTY_FILE_CID))
+        );
+        assert_eq!(
+            nft.media_hash_status.as_deref(),
+            Some(MEDIA_HASH_STATUS_VERIFIED)
+        );
+
+        nft.metadata.media_hash = Some(base64::encode([0; 32]));
+        resolve_nft_media(&mut nft, None, &gateways);
+        assert_eq!(
+            nft.media_hash_status.as_deref(),
+            Some(MEDIA_HASH_STATUS_MISMATCH)
+        );
+
+        nft.metadata.media = Some("https://example.com/1.png".to_string());
+        resolve_nft_media(&mut nft, None, &gateways);
+        assert_eq!(
+            nft.media_hash_status.as_deref(),
+            Some(MEDIA_HASH_STATUS_UNVERIFIED)
+        );
+
+        nft.metadata.media_hash = None;
+        resolve_nft_media(&mut nft, None, &gateways);
+        assert_eq!(
+            nft.media_hash_status.as_deref(),
+            Some(MEDIA_HASH_STATUS_NOT_PROVIDED)
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use crate::modules::nft;
 use crate::{errors, rpc_helpers};
 
+// near-sdk contracts panic this way if the metadata was never set
+const METADATA_NOT_SET_ERROR: &str = "called `Option::unwrap()` on a `None` value";
+
 pub(crate) async fn get_nft_contract_metadata(
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     contract_id: near_primitives::types::AccountId,
     block_height: u64,
 ) -> crate::Result<nft::schemas::NftContractMetadata> {
@@ -18,10 +21,7 @@ pub(crate) async fn get_nft_contract_metadata(
         Ok(response) => response,
         Err(err) => {
             println!("{}", err.message);
-            if err
-                .message
-                .contains("called `Option::unwrap()` on a `None` value")
-            {
+            if err.message.contains(METADATA_NOT_SET_ERROR) {
                 return Err(errors::ErrorKind::ContractError(
                     "The contract did not provide NFT Metadata which is a required part of NFT NEP 171".to_string(),
                 )
@@ -36,6 +36,36 @@ pub(crate) async fn get_nft_contract_metadata(
     )?)
 }
 
+/// Gives `base_uri` of the contract, we need it to build the media URLs.
+/// The contracts without NFT metadata do not have it, the other errors are returned as is
+pub(crate) async fn get_nft_base_uri(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    contract_id: &near_primitives::types::AccountId,
+    block_height: u64,
+) -> crate::Result<Option<String>> {
+    let request = rpc_helpers::get_function_call_request(
+        block_height,
+        contract_id.clone(),
+        "nft_metadata",
+        serde_json::json!({}),
+    );
+    match rpc_helpers::call_function(rpc_client, request, block_height, contract_id).await? {
+        rpc_helpers::FunctionCallResult::Success(response) => Ok(serde_json::from_slice::<
+            nft::schemas::NftContractMetadata,
+        >(&response.result)?
+        .base_uri),
+        rpc_helpers::FunctionCallResult::NotImplemented => Ok(None),
+        rpc_helpers::FunctionCallResult::Failed(vm_error)
+            if vm_error.contains(METADATA_NOT_SET_ERROR) =>
+        {
+            Ok(None)
+        }
+        rpc_helpers::FunctionCallResult::Failed(vm_error) => {
+            Err(errors::ErrorKind::ContractError(vm_error).into())
+        }
+    }
+}
+
 // Metadata is the required part of the standard.
 // Unfortunately, some contracts (e.g. `nft.nearapps.near`) do not implement it.
 // We should give at least anything for such contracts when we serve the overview information.
@@ -87,6 +117,23 @@ mod tests {
         insta::assert_debug_snapshot!(metadata);
     }
 
+    #[tokio::test]
+    async fn test_nft_base_uri() {
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let base_uri = get_nft_base_uri(&rpc_client, &contract, block.height)
+            .await
+            .unwrap();
+        assert_eq!(base_uri.as_deref(), Some("https://ipfs.fleek.co/ipfs"));
+
+        let contract = near_primitives::types::AccountId::from_str("nft.nearapps.near").unwrap();
+        let base_uri = get_nft_base_uri(&rpc_client, &contract, block.height)
+            .await
+            .unwrap();
+        assert!(base_uri.is_none());
+    }
+
     #[tokio::test]
     async fn test_nft_contract_metadata_broken_contract() {
         let rpc_client = init_rpc();
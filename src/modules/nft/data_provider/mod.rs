@@ -1,8 +1,20 @@
+mod collection;
+mod contract_history;
 mod history;
+mod holders;
+mod media;
 mod metadata;
 mod models;
 mod nft_info;
+mod reference;
+mod royalties;
 
+pub(crate) use collection::get_nft_collection;
+pub(crate) use contract_history::get_nft_contract_history;
 pub(crate) use history::get_nft_history;
-pub(crate) use metadata::get_nft_contract_metadata;
+pub(crate) use holders::get_nft_holders;
+pub(crate) use media::NftMediaContext;
+pub(crate) use metadata::{get_nft_base_uri, get_nft_contract_metadata};
 pub(crate) use nft_info::{get_nft, get_nfts_by_contract, get_nfts_count};
+pub(crate) use reference::{HttpReferenceFetcher, ReferenceResolver};
+pub(crate) use royalties::get_nft_royalties;
@@ -9,6 +9,7 @@ pub(crate) struct NftHistoryInfo {
     pub new_account_id: String,
     pub block_timestamp_nanos: BigDecimal,
     pub block_height: BigDecimal,
+    pub receipt_id: String,
 }
 
 #[derive(sqlx::FromRow)]
@@ -17,3 +18,35 @@ pub(crate) struct NftCount {
     pub count: i64,
     pub last_updated_at_timestamp: BigDecimal,
 }
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct NftContractHistoryInfo {
+    pub token_id: String,
+    pub cause: String,
+    pub status: String,
+    pub old_account_id: String,
+    pub new_account_id: String,
+    pub shard_id: BigDecimal,
+    pub index_in_shard: i32,
+    pub block_timestamp_nanos: BigDecimal,
+    pub block_height: BigDecimal,
+    pub receipt_id: String,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct NftHolderInfo {
+    pub total_holders: i64,
+    // false if the cursor account does not hold any NFT at the given block
+    pub cursor_found: bool,
+    // The query always returns at least one row with `total_holders`, even if the page is empty
+    pub account_id: Option<String>,
+    pub nft_count: Option<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct NftTokenInfo {
+    pub total_supply: i64,
+    // The query always returns at least one row with `total_supply`, even if the page is empty
+    pub token_id: Option<String>,
+    pub owner_account_id: Option<String>,
+}
@@ -4,10 +4,13 @@ use crate::modules::nft;
 use crate::{db_helpers, errors, rpc_helpers, types};
 use serde::{Deserialize, Serialize};
 
+// Abusive contracts may have thousands of approvals for one token
+const MAX_APPROVALS: usize = 100;
+
 // TODO PHASE 2 pagination by artificial index added to assets__non_fungible_token_events
 pub(crate) async fn get_nfts_count(
     db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     block: &db_helpers::Block,
     account_id: &near_primitives::types::AccountId,
     limit: u32,
@@ -91,11 +94,12 @@ pub(crate) async fn get_nfts_count(
 }
 
 pub(crate) async fn get_nfts_by_contract(
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     contract_id: near_primitives::types::AccountId,
     account_id: near_primitives::types::AccountId,
-    block_height: u64,
+    block: &db_helpers::Block,
     limit: u32,
+    media: &super::media::NftMediaContext<'_>,
 ) -> crate::Result<Vec<nft::schemas::Nft>> {
     // TODO PHASE 2 pagination
     // RPC supports pagination, but the order is defined by the each contract and we can't control it.
@@ -104,48 +108,125 @@ pub(crate) async fn get_nfts_by_contract(
     // right after that we can stop using RPC here.
     // Or, maybe we want to delegate this task fully to the contracts?
     let request = rpc_helpers::get_function_call_request(
-        block_height,
+        block.height,
         contract_id.clone(),
         "nft_tokens_for_owner",
         // https://nomicon.io/Standards/Tokens/NonFungibleToken/Enumeration
         serde_json::json!({ "account_id": account_id, "from_index": "0", "limit": limit }),
     );
     let response =
-        rpc_helpers::wrapped_call(rpc_client, request, block_height, &contract_id).await?;
+        rpc_helpers::wrapped_call(rpc_client, request, block.height, &contract_id).await?;
 
     let tokens = serde_json::from_slice::<Vec<Token>>(&response.result)?;
     let mut result = vec![];
     for token in tokens {
-        result.push(nft::schemas::Nft::try_from(token)?);
+        result.push(to_nft(token, block.timestamp, media)?);
     }
     Ok(result)
 }
 
 pub(crate) async fn get_nft(
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     contract_id: near_primitives::types::AccountId,
     token_id: String,
-    block_height: u64,
+    block: &db_helpers::Block,
+    with_approvals: bool,
+    media: &super::media::NftMediaContext<'_>,
 ) -> crate::Result<nft::schemas::Nft> {
+    get_implemented_nft(
+        rpc_client,
+        contract_id.clone(),
+        token_id,
+        block,
+        with_approvals,
+        media,
+    )
+    .await?
+    .ok_or_else(|| {
+        errors::ErrorKind::InvalidInput(format!(
+            "The account `{}` does not implement any suitable contract at block {}",
+            contract_id, block.height
+        ))
+        .into()
+    })
+}
+
+/// The same as `get_nft`, but gives None if the contract does not have `nft_token` method
+pub(super) async fn get_implemented_nft(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    contract_id: near_primitives::types::AccountId,
+    token_id: String,
+    block: &db_helpers::Block,
+    with_approvals: bool,
+    media: &super::media::NftMediaContext<'_>,
+) -> crate::Result<Option<nft::schemas::Nft>> {
     let request = rpc_helpers::get_function_call_request(
-        block_height,
+        block.height,
         contract_id.clone(),
         "nft_token",
         serde_json::json!({ "token_id": token_id }),
     );
     let response =
-        rpc_helpers::wrapped_call(rpc_client, request, block_height, &contract_id).await?;
+        match rpc_helpers::call_function(rpc_client, request, block.height, &contract_id).await? {
+            rpc_helpers::FunctionCallResult::Success(response) => response,
+            rpc_helpers::FunctionCallResult::NotImplemented => return Ok(None),
+            rpc_helpers::FunctionCallResult::Failed(vm_error) => {
+                return Err(errors::ErrorKind::ContractError(vm_error).into())
+            }
+        };
 
     match serde_json::from_slice::<Option<Token>>(&response.result)? {
         None => Err(errors::ErrorKind::InvalidInput(format!(
             "Token `{}` does not exist in contract `{}`, block_height {}",
-            token_id, contract_id, block_height
+            token_id, contract_id, block.height
         ))
         .into()),
-        Some(token) => nft::schemas::Nft::try_from(token),
+        Some(token) => {
+            let approvals = if with_approvals {
+                token.approved_account_ids.as_ref().map(sanitize_approvals)
+            } else {
+                None
+            };
+            let mut nft = to_nft(token, block.timestamp, media)?;
+            nft.approvals = approvals;
+            Ok(Some(nft))
+        }
     }
 }
 
+/// Contracts could put anything to `approved_account_ids`, so we skip the entries
+/// which do not look like `account_id: approval_id` and limit the number of the entries we show
+fn sanitize_approvals(approved_account_ids: &serde_json::Value) -> Vec<nft::schemas::NftApproval> {
+    let mut approvals: Vec<nft::schemas::NftApproval> = approved_account_ids
+        .as_object()
+        .map(|approved_account_ids| {
+            approved_account_ids
+                .iter()
+                .filter_map(|(account_id, approval_id)| {
+                    let approval_id = match approval_id {
+                        serde_json::Value::Number(number) => number.as_u64(),
+                        serde_json::Value::String(string) => string.parse::<u64>().ok(),
+                        _ => None,
+                    }?;
+                    Some(nft::schemas::NftApproval {
+                        account_id: types::AccountId::from_str(account_id).ok()?,
+                        approval_id: approval_id.into(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // The latest approvals are the most relevant ones
+    approvals.sort_by(|a, b| {
+        b.approval_id
+            .0
+            .cmp(&a.approval_id.0)
+            .then_with(|| a.account_id.cmp(&b.account_id))
+    });
+    approvals.truncate(MAX_APPROVALS);
+    approvals
+}
+
 // Taken from https://github.com/near/near-sdk-rs/blob/master/near-contract-standards/src/non_fungible_token/token.rs
 /// Note that token IDs for NFTs are strings on NEAR. It's still fine to use autoincrementing numbers as unique IDs if desired, but they should be stringified. This is to make IDs more future-proof as chain-agnostic conventions and standards arise, and allows for more flexibility with considerations like bridging NFTs across chains, etc.
 pub type TokenId = String;
@@ -156,31 +237,63 @@ pub struct Token {
     pub token_id: TokenId,
     pub owner_id: types::AccountId,
     pub metadata: Option<nft::schemas::NftMetadata>,
-    pub approved_account_ids: Option<std::collections::HashMap<types::AccountId, u64>>,
+    // We do not parse it here because the contracts often put weird things there
+    pub approved_account_ids: Option<serde_json::Value>,
 }
 
-impl TryFrom<Token> for nft::schemas::Nft {
-    type Error = errors::Error;
+/// Converts the token from the contract to our representation.
+/// The validity of the NFT is checked against the given block timestamp
+pub(super) fn to_nft(
+    token: Token,
+    block_timestamp: u64,
+    media: &super::media::NftMediaContext<'_>,
+) -> crate::Result<nft::schemas::Nft> {
+    let metadata = token.metadata.ok_or_else(|| {
+        errors::ErrorKind::ContractError(
+            "The contract did not provide NFT Metadata which is a required part of NFT NEP 171"
+                .to_string(),
+        )
+    })?;
 
-    fn try_from(token: Token) -> crate::Result<Self> {
-        let metadata = token.metadata.ok_or_else(|| {
-            errors::ErrorKind::ContractError(
-                "The contract did not provide NFT Metadata which is a required part of NFT NEP 171"
-                    .to_string(),
-            )
-        })?;
+    let mut nft = nft::schemas::Nft {
+        token_id: token.token_id,
+        owner_account_id: token.owner_id.0.to_string(),
+        validity: get_nft_validity(&metadata, block_timestamp),
+        metadata,
+        media_url: None,
+        media_hash_status: None,
+        approvals: None,
+        royalties: None,
+        resolved_reference: None,
+    };
+    super::media::resolve_nft_media(&mut nft, media.base_uri, media.gateways);
+    Ok(nft)
+}
 
-        Ok(Self {
-            token_id: token.token_id,
-            owner_account_id: token.owner_id.0.to_string(),
-            metadata,
-        })
+/// Normalizes NEP-177 timestamps; the NFT is valid if `starts_at` <= block timestamp < `expires_at`.
+/// The timestamps which could not be parsed are ignored
+pub(super) fn get_nft_validity(
+    metadata: &nft::schemas::NftMetadata,
+    block_timestamp: u64,
+) -> nft::schemas::NftValidity {
+    let parse =
+        |value: &Option<String>| value.as_deref().and_then(types::timestamp::parse_to_nanos);
+    let starts_at = parse(&metadata.starts_at);
+    let expires_at = parse(&metadata.expires_at);
+    nft::schemas::NftValidity {
+        issued_at_nanos: parse(&metadata.issued_at).map(types::U64::from),
+        starts_at_nanos: starts_at.map(types::U64::from),
+        expires_at_nanos: expires_at.map(types::U64::from),
+        updated_at_nanos: parse(&metadata.updated_at).map(types::U64::from),
+        is_valid: starts_at.map_or(true, |starts_at| starts_at <= block_timestamp)
+            && expires_at.map_or(true, |expires_at| block_timestamp < expires_at),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config;
     use crate::modules::tests::*;
 
     #[tokio::test]
@@ -214,6 +327,7 @@ mod tests {
         let block = db_helpers::Block {
             timestamp: 1655571176644255779,
             height: 68000000,
+            finality: crate::types::query_params::Finality::Final,
         };
         let account = near_primitives::types::AccountId::from_str("vlad.near").unwrap();
 
@@ -240,7 +354,13 @@ mod tests {
             near_primitives::types::AccountId::from_str("billionairebullsclub.near").unwrap();
         let account = near_primitives::types::AccountId::from_str("olenavorobei.near").unwrap();
 
-        let nfts = get_nfts_by_contract(&rpc_client, contract, account, block.height, 4).await;
+        let gateways = config::NftGatewaysConfig::default();
+        let media = super::super::media::NftMediaContext {
+            base_uri: None,
+            gateways: &gateways,
+        };
+
+        let nfts = get_nfts_by_contract(&rpc_client, contract, account, &block, 4, &media).await;
         insta::assert_debug_snapshot!(nfts);
     }
 
@@ -251,7 +371,13 @@ mod tests {
         let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
         let token = "415815:1".to_string();
 
-        let nft = get_nft(&rpc_client, contract, token, block.height).await;
+        let gateways = config::NftGatewaysConfig::default();
+        let media = super::super::media::NftMediaContext {
+            base_uri: None,
+            gateways: &gateways,
+        };
+
+        let nft = get_nft(&rpc_client, contract, token, &block, false, &media).await;
         insta::assert_debug_snapshot!(nft);
     }
 
@@ -262,7 +388,69 @@ mod tests {
         let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
         let token = "no_such_token".to_string();
 
-        let nft = get_nft(&rpc_client, contract, token, block.height).await;
+        let gateways = config::NftGatewaysConfig::default();
+        let media = super::super::media::NftMediaContext {
+            base_uri: None,
+            gateways: &gateways,
+        };
+
+        let nft = get_nft(&rpc_client, contract, token, &block, false, &media).await;
         insta::assert_debug_snapshot!(nft);
     }
+
+    #[test]
+    fn test_sanitize_approvals() {
+        let approved_account_ids = serde_json::json!({
+            "marketplace.near": 3,
+            "other.near": "5",
+            "Not An Account": 7,
+            "weird.near": { "approval_id": 9 },
+        });
+        let approvals = sanitize_approvals(&approved_account_ids);
+        assert_eq!(
+            approvals
+                .iter()
+                .map(|approval| (approval.account_id.to_string(), approval.approval_id.0))
+                .collect::<Vec<_>>(),
+            vec![
+                ("other.near".to_string(), 5),
+                ("marketplace.near".to_string(), 3),
+            ]
+        );
+        assert!(sanitize_approvals(&serde_json::json!(["marketplace.near"])).is_empty());
+    }
+
+    #[test]
+    fn test_sanitize_approvals_limit() {
+        let approved_account_ids: serde_json::Map<String, serde_json::Value> = (0..MAX_APPROVALS
+            * 2)
+            .map(|i| (format!("account{}.near", i), serde_json::json!(i)))
+            .collect();
+        let approvals = sanitize_approvals(&serde_json::Value::Object(approved_account_ids));
+        assert_eq!(approvals.len(), MAX_APPROVALS);
+        assert_eq!(approvals[0].approval_id.0, (MAX_APPROVALS * 2 - 1) as u64);
+    }
+
+    #[test]
+    fn test_nft_validity() {
+        let metadata: nft::schemas::NftMetadata = serde_json::from_value(serde_json::json!({
+            "title": "Ticket",
+            "issued_at": 1656633600000_u64,
+            "starts_at": "2022-07-01T00:00:00Z",
+            "expires_at": "1656720000000",
+            "updated_at": "not a date",
+        }))
+        .unwrap();
+        let starts_at = 1_656_633_600_000_000_000;
+        let expires_at = 1_656_720_000_000_000_000;
+
+        let validity = get_nft_validity(&metadata, starts_at);
+        assert_eq!(validity.issued_at_nanos, Some(starts_at.into()));
+        assert_eq!(validity.starts_at_nanos, Some(starts_at.into()));
+        assert_eq!(validity.expires_at_nanos, Some(expires_at.into()));
+        assert_eq!(validity.updated_at_nanos, None);
+        assert!(validity.is_valid);
+        assert!(!get_nft_validity(&metadata, starts_at - 1).is_valid);
+        assert!(!get_nft_validity(&metadata, expires_at).is_valid);
+    }
 }
@@ -0,0 +1,692 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+use sha2::Digest;
+
+use crate::modules::nft;
+use crate::{config, types};
+
+const HASH_STATUS_VERIFIED: &str = "VERIFIED";
+const HASH_STATUS_MISMATCH: &str = "MISMATCH";
+const HASH_STATUS_NOT_PROVIDED: &str = "NOT_PROVIDED";
+// We could not load the document, so there is nothing to check
+const HASH_STATUS_NOT_CHECKED: &str = "NOT_CHECKED";
+// The contract controls the URL, so each redirect is checked not to lead to our internal network
+const MAX_REDIRECTS: usize = 5;
+
+/// Loads the document by URL.
+/// We use HTTP in production, tests could replace it with the local stand-in
+pub(crate) trait ReferenceFetcher: Send + Sync {
+    /// Should fail as soon as the content becomes bigger than `max_size` bytes
+    fn fetch<'a>(&'a self, url: &'a str, max_size: usize)
+        -> BoxFuture<'a, Result<Vec<u8>, String>>;
+}
+
+/// Loads the documents only from the public addresses.
+/// The addresses are checked both for the original URL and for each redirect
+pub(crate) struct HttpReferenceFetcher {
+    client: reqwest::Client,
+    allow_private_addresses: bool,
+}
+
+impl HttpReferenceFetcher {
+    pub(crate) fn new() -> Self {
+        let client = reqwest::Client::builder()
+            // The resolver is used for each connection, so DNS rebinding does not help to bypass the check
+            .dns_resolver(Arc::new(PublicAddressResolver))
+            .redirect(reqwest::redirect::Policy::custom(|attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error(format!("More than {} redirects", MAX_REDIRECTS));
+                }
+                match check_url(attempt.url()) {
+                    Ok(()) => attempt.follow(),
+                    Err(err) => attempt.error(err),
+                }
+            }))
+            .build()
+            .expect("Could not build HTTP client for NFT references");
+        Self {
+            client,
+            allow_private_addresses: false,
+        }
+    }
+
+    // The tests serve the documents from localhost
+    #[cfg(test)]
+    fn allowing_private_addresses() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            allow_private_addresses: true,
+        }
+    }
+}
+
+impl ReferenceFetcher for HttpReferenceFetcher {
+    fn fetch<'a>(
+        &'a self,
+        url: &'a str,
+        max_size: usize,
+    ) -> BoxFuture<'a, Result<Vec<u8>, String>> {
+        Box::pin(async move {
+            if !self.allow_private_addresses {
+                // The resolver is not called for IP literals, so we check them here
+                check_url(&reqwest::Url::parse(url).map_err(|err| err.to_string())?)?;
+            }
+            let mut response = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|err| err.to_string())?;
+            if response
+                .content_length()
+                .map_or(false, |length| length > max_size as u64)
+            {
+                return Err(get_too_big_error(max_size));
+            }
+            // Content-Length could be missing or wrong, so we check the size while reading
+            let mut content = vec![];
+            while let Some(chunk) = response.chunk().await.map_err(|err| err.to_string())? {
+                if content.len() + chunk.len() > max_size {
+                    return Err(get_too_big_error(max_size));
+                }
+                content.extend_from_slice(&chunk);
+            }
+            Ok(content)
+        })
+    }
+}
+
+struct PublicAddressResolver;
+
+impl reqwest::dns::Resolve for PublicAddressResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(&addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(
+                    format!("{} does not resolve to a public address", name.as_str()).into(),
+                );
+            }
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(addrs)
+        })
+    }
+}
+
+/// Accepts only HTTP(S) URLs. If the host is an IP address, it should be a public one
+fn check_url(url: &reqwest::Url) -> Result<(), String> {
+    if url.scheme() != "https" && url.scheme() != "http" {
+        return Err(format!("HTTP(S) URL is expected, got {}", url));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("URL without host: {}", url))?;
+    // IPv6 hosts are given in brackets
+    if let Ok(ip) = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        if !is_public_ip(&ip) {
+            return Err(format!("{} is not a public address", ip));
+        }
+    }
+    Ok(())
+}
+
+fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: &Ipv4Addr) -> bool {
+    let octets = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // 0.0.0.0/8 "this network"
+        || octets[0] == 0
+        // 100.64.0.0/10 carrier-grade NAT
+        || (octets[0] == 100 && (octets[1] & 0b1100_0000) == 64)
+        // 240.0.0.0/4 reserved
+        || octets[0] >= 240)
+}
+
+fn is_public_ipv6(ip: &Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    // ::ffff:a.b.c.d is IPv4 address in disguise
+    if segments[..5].iter().all(|segment| *segment == 0) && segments[5] == 0xffff {
+        let [a, b] = segments[6].to_be_bytes();
+        let [c, d] = segments[7].to_be_bytes();
+        return is_public_ipv4(&Ipv4Addr::new(a, b, c, d));
+    }
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // fc00::/7 unique local
+        || (segments[0] & 0xfe00) == 0xfc00
+        // fe80::/10 link-local
+        || (segments[0] & 0xffc0) == 0xfe80)
+}
+
+struct CachedDocument {
+    sha256: Vec<u8>,
+    document: serde_json::Value,
+}
+
+#[derive(Default)]
+struct DocumentsCache {
+    documents: HashMap<String, Arc<CachedDocument>>,
+    // The oldest documents go first, we drop them when the cache is full
+    urls: VecDeque<String>,
+    // We do not hammer the broken URLs on each request: the error is kept until the given time
+    failures: HashMap<String, (std::time::Instant, String)>,
+}
+
+/// Loads the off-chain JSON referenced by NFT metadata (NEP-177), verifies it by `reference_hash`.
+/// The successfully loaded documents are cached by URL, the failures are cached for `failure_cache_ttl_ms`
+pub struct ReferenceResolver {
+    fetcher: Arc<dyn ReferenceFetcher>,
+    config: config::NftReferenceConfig,
+    gateways: config::NftGatewaysConfig,
+    cache: Mutex<DocumentsCache>,
+}
+
+impl ReferenceResolver {
+    pub(crate) fn new(
+        fetcher: Arc<dyn ReferenceFetcher>,
+        config: config::NftReferenceConfig,
+        gateways: config::NftGatewaysConfig,
+    ) -> Self {
+        Self {
+            fetcher,
+            config,
+            gateways,
+            cache: Mutex::new(DocumentsCache::default()),
+        }
+    }
+
+    /// Returns None if the NFT has no reference.
+    /// The problems with loading the document do not fail the request, we show them in `error`
+    pub(crate) async fn resolve(
+        &self,
+        metadata: &nft::schemas::NftMetadata,
+        base_uri: Option<&str>,
+    ) -> Option<nft::schemas::NftResolvedReference> {
+        let reference = metadata.reference.as_deref()?;
+        let url = get_reference_url(reference, base_uri, &self.gateways);
+        let cached_document = match self.get_document(&url).await {
+            Ok(cached_document) => cached_document,
+            Err(error) => {
+                return Some(nft::schemas::NftResolvedReference {
+                    url,
+                    hash_status: HASH_STATUS_NOT_CHECKED.to_string(),
+                    document: None,
+                    error: Some(error),
+                })
+            }
+        };
+
+        let hash_status = match &metadata.reference_hash {
+            None => HASH_STATUS_NOT_PROVIDED,
+            Some(reference_hash) => match base64::decode(reference_hash) {
+                Ok(expected_sha256) if expected_sha256 == cached_document.sha256 => {
+                    HASH_STATUS_VERIFIED
+                }
+                _ => HASH_STATUS_MISMATCH,
+            },
+        };
+        // We do not show the document which differs from the one the contract refers to
+        let document = if hash_status == HASH_STATUS_MISMATCH {
+            None
+        } else {
+            Some(types::JsonValue(cached_document.document.clone()))
+        };
+        Some(nft::schemas::NftResolvedReference {
+            url,
+            hash_status: hash_status.to_string(),
+            document,
+            error: None,
+        })
+    }
+
+    async fn get_document(&self, url: &str) -> Result<Arc<CachedDocument>, String> {
+        {
+            let cache = self.cache.lock().unwrap();
+            if let Some(cached_document) = cache.documents.get(url) {
+                return Ok(cached_document.clone());
+            }
+            if let Some((expires_at, error)) = cache.failures.get(url) {
+                if std::time::Instant::now() < *expires_at {
+                    return Err(error.clone());
+                }
+            }
+        }
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(format!(
+                "HTTP(S) URL is expected, got {}. Check `base_uri` of the contract",
+                url
+            ));
+        }
+
+        let result = self.load_document(url).await;
+        let mut cache = self.cache.lock().unwrap();
+        match &result {
+            Ok(cached_document) => {
+                cache.failures.remove(url);
+                if self.config.cache_capacity > 0 && !cache.documents.contains_key(url) {
+                    if cache.urls.len() >= self.config.cache_capacity {
+                        if let Some(oldest_url) = cache.urls.pop_front() {
+                            cache.documents.remove(&oldest_url);
+                        }
+                    }
+                    cache.urls.push_back(url.to_string());
+                    cache
+                        .documents
+                        .insert(url.to_string(), cached_document.clone());
+                }
+            }
+            Err(error) => {
+                let now = std::time::Instant::now();
+                if cache.failures.len() >= self.config.cache_capacity {
+                    cache
+                        .failures
+                        .retain(|_, (expires_at, _)| now < *expires_at);
+                }
+                if cache.failures.len() < self.config.cache_capacity {
+                    cache.failures.insert(
+                        url.to_string(),
+                        (
+                            now + std::time::Duration::from_millis(
+                                self.config.failure_cache_ttl_ms,
+                            ),
+                            error.clone(),
+                        ),
+                    );
+                }
+            }
+        }
+        result
+    }
+
+    async fn load_document(&self, url: &str) -> Result<Arc<CachedDocument>, String> {
+        let content = tokio::time::timeout(
+            std::time::Duration::from_millis(self.config.timeout_ms),
+            self.fetcher.fetch(url, self.config.max_size_bytes),
+        )
+        .await
+        .map_err(|_| {
+            format!(
+                "The reference is not loaded within {} ms",
+                self.config.timeout_ms
+            )
+        })??;
+        // We can't rely on the fetcher here, the budget should be strict
+        if content.len() > self.config.max_size_bytes {
+            return Err(get_too_big_error(self.config.max_size_bytes));
+        }
+        let document = serde_json::from_slice::<serde_json::Value>(&content)
+            .map_err(|err| format!("The reference is not a valid JSON: {}", err))?;
+        Ok(Arc::new(CachedDocument {
+            sha256: sha2::Sha256::digest(&content).to_vec(),
+            document,
+        }))
+    }
+}
+
+/// `reference` is normalized the same way as `media`.
+/// If we could not build the URL, we show `reference` as is together with the error
+fn get_reference_url(
+    reference: &str,
+    base_uri: Option<&str>,
+    gateways: &config::NftGatewaysConfig,
+) -> String {
+    super::media::get_absolute_url(reference, base_uri, gateways)
+        .unwrap_or_else(|| reference.trim().to_string())
+}
+
+fn get_too_big_error(max_size: usize) -> String {
+    format!("The reference is bigger than {} bytes", max_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const DOCUMENT: &str = r#"{"attributes": [{"trait_type": "Eyes", "value": "Blue"}]}"#;
+
+    #[derive(Default)]
+    struct StaticFetcher {
+        contents: HashMap<String, Vec<u8>>,
+        calls: AtomicUsize,
+    }
+
+    impl ReferenceFetcher for StaticFetcher {
+        fn fetch<'a>(
+            &'a self,
+            url: &'a str,
+            _max_size: usize,
+        ) -> BoxFuture<'a, Result<Vec<u8>, String>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let content = self
+                .contents
+                .get(url)
+                .cloned()
+                .ok_or_else(|| "404 Not Found".to_string());
+            Box::pin(async move { content })
+        }
+    }
+
+    struct SlowFetcher;
+
+    impl ReferenceFetcher for SlowFetcher {
+        fn fetch<'a>(
+            &'a self,
+            _url: &'a str,
+            _max_size: usize,
+        ) -> BoxFuture<'a, Result<Vec<u8>, String>> {
+            Box::pin(async move {
+                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                Ok(DOCUMENT.as_bytes().to_vec())
+            })
+        }
+    }
+
+    fn get_metadata(reference: &str, reference_hash: Option<String>) -> nft::schemas::NftMetadata {
+        serde_json::from_value(serde_json::json!({
+            "reference": reference,
+            "reference_hash": reference_hash,
+        }))
+        .unwrap()
+    }
+
+    fn get_resolver(fetcher: Arc<dyn ReferenceFetcher>) -> ReferenceResolver {
+        ReferenceResolver::new(
+            fetcher,
+            config::NftReferenceConfig::default(),
+            config::NftGatewaysConfig::default(),
+        )
+    }
+
+    fn get_static_fetcher() -> Arc<StaticFetcher> {
+        let mut fetcher = StaticFetcher::default();
+        fetcher.contents.insert(
+            "https://example.com/nft/1.json".to_string(),
+            DOCUMENT.as_bytes().to_vec(),
+        );
+        Arc::new(fetcher)
+    }
+
+    fn get_document_hash() -> String {
+        base64::encode(sha2::Sha256::digest(DOCUMENT.as_bytes()))
+    }
+
+    #[test]
+    fn test_reference_url() {
+        let gateways = config::NftGatewaysConfig::default();
+        assert_eq!(
+            get_reference_url("1.json", Some("https://example.com/nft/"), &gateways),
+            "https://example.com/nft/1.json"
+        );
+        assert_eq!(
+            get_reference_url("/1.json", Some("https://example.com/nft"), &gateways),
+            "https://example.com/nft/1.json"
+        );
+        assert_eq!(
+            get_reference_url(
+                "https://other.com/1.json",
+                Some("https://example.com/nft"),
+                &gateways
+            ),
+            "https://other.com/1.json"
+        );
+        assert_eq!(
+            get_reference_url(
+                "ipfs://bafkreieg5ow66ophfn72numreyhts5vwuflwh7jhsa6r5zfjldxlkslfli",
+                None,
+                &gateways
+            ),
+            "https://ipfs.io/ipfs/bafkreieg5ow66ophfn72numreyhts5vwuflwh7jhsa6r5zfjldxlkslfli"
+        );
+        assert_eq!(get_reference_url("1.json", None, &gateways), "1.json");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_verified() {
+        let resolver = get_resolver(get_static_fetcher());
+        let metadata = get_metadata("1.json", Some(get_document_hash()));
+
+        let resolved = resolver
+            .resolve(&metadata, Some("https://example.com/nft"))
+            .await
+            .unwrap();
+        assert_eq!(resolved.hash_status, HASH_STATUS_VERIFIED);
+        assert_eq!(
+            resolved.document.unwrap().0,
+            serde_json::from_str::<serde_json::Value>(DOCUMENT).unwrap()
+        );
+        assert!(resolved.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_hash_mismatch() {
+        let resolver = get_resolver(get_static_fetcher());
+        let metadata = get_metadata(
+            "https://example.com/nft/1.json",
+            Some(base64::encode(sha2::Sha256::digest(b"{}"))),
+        );
+
+        let resolved = resolver.resolve(&metadata, None).await.unwrap();
+        assert_eq!(resolved.hash_status, HASH_STATUS_MISMATCH);
+        assert!(resolved.document.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_without_hash() {
+        let resolver = get_resolver(get_static_fetcher());
+        let metadata = get_metadata("https://example.com/nft/1.json", None);
+
+        let resolved = resolver.resolve(&metadata, None).await.unwrap();
+        assert_eq!(resolved.hash_status, HASH_STATUS_NOT_PROVIDED);
+        assert!(resolved.document.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_cached() {
+        let fetcher = get_static_fetcher();
+        let resolver = get_resolver(fetcher.clone());
+        let metadata = get_metadata("https://example.com/nft/1.json", None);
+
+        resolver.resolve(&metadata, None).await.unwrap();
+        resolver.resolve(&metadata, None).await.unwrap();
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_errors() {
+        let fetcher = get_static_fetcher();
+        let resolver = get_resolver(fetcher.clone());
+
+        let resolved = resolver
+            .resolve(&get_metadata("1.json", None), None)
+            .await
+            .unwrap();
+        assert_eq!(resolved.hash_status, HASH_STATUS_NOT_CHECKED);
+        assert!(resolved.error.is_some());
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), 0);
+
+        let resolved = resolver
+            .resolve(&get_metadata("https://example.com/nft/2.json", None), None)
+            .await
+            .unwrap();
+        assert!(resolved.document.is_none());
+        assert!(resolved.error.is_some());
+
+        assert!(resolver
+            .resolve(&get_metadata("", None), None)
+            .await
+            .is_some());
+        let mut metadata = get_metadata("", None);
+        metadata.reference = None;
+        assert!(resolver.resolve(&metadata, None).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_timeout() {
+        let resolver = ReferenceResolver::new(
+            Arc::new(SlowFetcher),
+            config::NftReferenceConfig {
+                timeout_ms: 10,
+                ..Default::default()
+            },
+            config::NftGatewaysConfig::default(),
+        );
+        let resolved = resolver
+            .resolve(&get_metadata("https://example.com/nft/1.json", None), None)
+            .await
+            .unwrap();
+        assert!(resolved.document.is_none());
+        assert!(resolved.error.unwrap().contains("10 ms"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_too_big() {
+        let resolver = ReferenceResolver::new(
+            get_static_fetcher(),
+            config::NftReferenceConfig {
+                max_size_bytes: 10,
+                ..Default::default()
+            },
+            config::NftGatewaysConfig::default(),
+        );
+        let resolved = resolver
+            .resolve(&get_metadata("https://example.com/nft/1.json", None), None)
+            .await
+            .unwrap();
+        assert!(resolved.document.is_none());
+        assert_eq!(resolved.error.unwrap(), get_too_big_error(10));
+    }
+
+    // Serves the given body once, so that we can check the HTTP fetcher without the network
+    async fn serve_once(body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+            assert!(read > 0);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{}/1.json", addr)
+    }
+
+    #[tokio::test]
+    async fn test_http_fetcher() {
+        let url = serve_once(DOCUMENT).await;
+        let content = HttpReferenceFetcher::allowing_private_addresses()
+            .fetch(&url, 1024)
+            .await
+            .unwrap();
+        assert_eq!(content, DOCUMENT.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_http_fetcher_too_big() {
+        let url = serve_once(DOCUMENT).await;
+        let error = HttpReferenceFetcher::allowing_private_addresses()
+            .fetch(&url, 10)
+            .await
+            .unwrap_err();
+        assert_eq!(error, get_too_big_error(10));
+    }
+
+    #[tokio::test]
+    async fn test_http_fetcher_private_address() {
+        let url = serve_once(DOCUMENT).await;
+        let error = HttpReferenceFetcher::new()
+            .fetch(&url, 1024)
+            .await
+            .unwrap_err();
+        assert_eq!(error, "127.0.0.1 is not a public address");
+    }
+
+    #[test]
+    fn test_check_url() {
+        // The same check is applied to each redirect
+        let error =
+            check_url(&reqwest::Url::parse("http://169.254.169.254/latest/meta-data").unwrap())
+                .unwrap_err();
+        assert_eq!(error, "169.254.169.254 is not a public address");
+        assert!(check_url(&reqwest::Url::parse("http://[::1]:8080/").unwrap()).is_err());
+        assert!(check_url(&reqwest::Url::parse("file:///etc/passwd").unwrap()).is_err());
+        assert!(check_url(&reqwest::Url::parse("https://example.com/nft/1.json").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(&ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["8.8.8.8", "104.16.1.1", "2606:4700::1111"] {
+            assert!(is_public_ip(&ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_failure_cached() {
+        let fetcher = get_static_fetcher();
+        let resolver = get_resolver(fetcher.clone());
+        let metadata = get_metadata("https://example.com/nft/2.json", None);
+
+        let resolved = resolver.resolve(&metadata, None).await.unwrap();
+        assert!(resolved.error.is_some());
+        let resolved = resolver.resolve(&metadata, None).await.unwrap();
+        assert!(resolved.error.is_some());
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), 1);
+
+        let resolver = ReferenceResolver::new(
+            fetcher.clone(),
+            config::NftReferenceConfig {
+                failure_cache_ttl_ms: 0,
+                ..Default::default()
+            },
+            config::NftGatewaysConfig::default(),
+        );
+        resolver.resolve(&metadata, None).await.unwrap();
+        resolver.resolve(&metadata, None).await.unwrap();
+        assert_eq!(fetcher.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::modules::nft;
+use crate::{rpc_helpers, types};
+
+const BASIS_POINTS_TOTAL: u128 = 10_000;
+// We ask the contract to split 1 NEAR, so that the rounding does not affect the basis points
+const NOTIONAL_BALANCE: u128 = 10_u128.pow(24);
+// The contracts usually panic if they have more royalty receivers than `max_len_payout`
+const MAX_LEN_PAYOUT: u32 = 50;
+
+// https://nomicon.io/Standards/Tokens/NonFungibleToken/Payout
+#[derive(serde::Deserialize)]
+struct Payout {
+    payout: std::collections::HashMap<types::AccountId, types::U128>,
+}
+
+/// Returns the royalty splits for the given NFT.
+/// We use NEP-199 `nft_payout` if the contract supports it, otherwise we try `nft_royalties`.
+/// Returns None if the contract implements neither of them or both of them fail:
+/// the royalties are optional, they should not break the whole NFT response
+pub(crate) async fn get_nft_royalties(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    contract_id: &near_primitives::types::AccountId,
+    nft: &nft::schemas::Nft,
+    block_height: u64,
+) -> crate::Result<Option<Vec<nft::schemas::NftRoyalty>>> {
+    let request = rpc_helpers::get_function_call_request(
+        block_height,
+        contract_id.clone(),
+        "nft_payout",
+        serde_json::json!({
+            "token_id": nft.token_id,
+            "balance": NOTIONAL_BALANCE.to_string(),
+            "max_len_payout": MAX_LEN_PAYOUT,
+        }),
+    );
+    match rpc_helpers::call_function(rpc_client, request, block_height, contract_id).await? {
+        rpc_helpers::FunctionCallResult::Success(response) => {
+            let payout = serde_json::from_slice::<Payout>(&response.result)?;
+            return Ok(Some(normalize_payout(payout)));
+        }
+        rpc_helpers::FunctionCallResult::NotImplemented => {}
+        rpc_helpers::FunctionCallResult::Failed(vm_error) => {
+            // e.g. the contract has more royalty receivers than `max_len_payout`
+            tracing::warn!(
+                target: crate::LOGGER_MSG,
+                "nft_payout failed for {} at contract {}, block {}: {}",
+                nft.token_id,
+                contract_id,
+                block_height,
+                vm_error
+            );
+        }
+    }
+
+    let request = rpc_helpers::get_function_call_request(
+        block_height,
+        contract_id.clone(),
+        "nft_royalties",
+        serde_json::json!({ "token_id": nft.token_id }),
+    );
+    match rpc_helpers::call_function(rpc_client, request, block_height, contract_id).await? {
+        rpc_helpers::FunctionCallResult::Success(response) => {
+            // Not standardized, but the contracts usually return the basis points by account
+            let royalties = serde_json::from_slice::<
+                std::collections::HashMap<types::AccountId, u32>,
+            >(&response.result)?;
+            Ok(Some(add_owner_share(royalties, &nft.owner_account_id)?))
+        }
+        rpc_helpers::FunctionCallResult::NotImplemented => Ok(None),
+        rpc_helpers::FunctionCallResult::Failed(vm_error) => {
+            tracing::warn!(
+                target: crate::LOGGER_MSG,
+                "nft_royalties failed for {} at contract {}, block {}: {}",
+                nft.token_id,
+                contract_id,
+                block_height,
+                vm_error
+            );
+            Ok(None)
+        }
+    }
+}
+
+fn normalize_payout(payout: Payout) -> Vec<nft::schemas::NftRoyalty> {
+    // The contract could give a bit less than the requested balance because of its own rounding,
+    // so we split 10000 basis points proportionally to what it gave
+    to_basis_points(
+        payout
+            .payout
+            .into_iter()
+            .map(|(account_id, amount)| (account_id, amount.0.min(NOTIONAL_BALANCE)))
+            .collect(),
+    )
+}
+
+fn add_owner_share(
+    mut royalties: std::collections::HashMap<types::AccountId, u32>,
+    owner_account_id: &str,
+) -> crate::Result<Vec<nft::schemas::NftRoyalty>> {
+    let royalties_total: u128 = royalties.values().map(|share| *share as u128).sum();
+    if royalties_total < BASIS_POINTS_TOTAL {
+        let owner_account_id: types::AccountId = owner_account_id.parse()?;
+        *royalties.entry(owner_account_id).or_default() +=
+            (BASIS_POINTS_TOTAL - royalties_total) as u32;
+    }
+    // If the royalties exceed 10000 basis points, the owner gets nothing
+    // and the royalties are scaled down proportionally
+    Ok(to_basis_points(
+        royalties
+            .into_iter()
+            .map(|(account_id, share)| (account_id, share as u128))
+            .collect(),
+    ))
+}
+
+/// Splits 10000 basis points proportionally to the given shares.
+/// The rounding remainder goes to the shares with the largest fractional parts,
+/// so the result always sums up to exactly 10000
+fn to_basis_points(shares: Vec<(types::AccountId, u128)>) -> Vec<nft::schemas::NftRoyalty> {
+    let total: u128 = shares.iter().map(|(_, share)| *share).sum();
+    if total == 0 {
+        return vec![];
+    }
+    // (account_id, basis_points rounded down, fractional part)
+    let mut split: Vec<(types::AccountId, u128, u128)> = shares
+        .into_iter()
+        .map(|(account_id, share)| {
+            let points = share * BASIS_POINTS_TOTAL;
+            (account_id, points / total, points % total)
+        })
+        .collect();
+    let distributed: u128 = split.iter().map(|(_, basis_points, _)| *basis_points).sum();
+    split.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    for (_, basis_points, _) in split
+        .iter_mut()
+        .take((BASIS_POINTS_TOTAL - distributed) as usize)
+    {
+        *basis_points += 1;
+    }
+
+    let mut result: Vec<nft::schemas::NftRoyalty> = split
+        .into_iter()
+        .filter(|(_, basis_points, _)| *basis_points > 0)
+        .map(|(account_id, basis_points, _)| nft::schemas::NftRoyalty {
+            account_id,
+            basis_points: basis_points as u32,
+        })
+        .collect();
+    sort_royalties(&mut result);
+    result
+}
+
+// HashMap does not keep the order, we want to have the same response for the same request
+fn sort_royalties(royalties: &mut [nft::schemas::NftRoyalty]) {
+    royalties.sort_by(|a, b| {
+        b.basis_points
+            .cmp(&a.basis_points)
+            .then_with(|| a.account_id.cmp(&b.account_id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_normalize_payout() {
+        let payout = serde_json::from_value::<Payout>(serde_json::json!({
+            "payout": {
+                "owner.near": "900000000000000000000000",
+                "artist.near": "100000000000000000000000",
+            }
+        }))
+        .unwrap();
+        let royalties = normalize_payout(payout);
+        assert_eq!(royalties.len(), 2);
+        assert_eq!(royalties[0].account_id.to_string(), "owner.near");
+        assert_eq!(royalties[0].basis_points, 9000);
+        assert_eq!(royalties[1].account_id.to_string(), "artist.near");
+        assert_eq!(royalties[1].basis_points, 1000);
+    }
+
+    #[test]
+    fn test_add_owner_share() {
+        let royalties = serde_json::from_value::<std::collections::HashMap<types::AccountId, u32>>(
+            serde_json::json!({ "artist.near": 500, "platform.near": 250 }),
+        )
+        .unwrap();
+        let royalties = add_owner_share(royalties, "owner.near").unwrap();
+        assert_eq!(
+            royalties
+                .iter()
+                .map(|royalty| (royalty.account_id.to_string(), royalty.basis_points))
+                .collect::<Vec<_>>(),
+            vec![
+                ("owner.near".to_string(), 9250),
+                ("artist.near".to_string(), 500),
+                ("platform.near".to_string(), 250),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_payout_equal_shares() {
+        let payout = serde_json::from_value::<Payout>(serde_json::json!({
+            "payout": {
+                "a.near": "333333333333333333333333",
+                "b.near": "333333333333333333333333",
+                "c.near": "333333333333333333333334",
+            }
+        }))
+        .unwrap();
+        let royalties = normalize_payout(payout);
+        assert_eq!(
+            royalties
+                .iter()
+                .map(|royalty| (royalty.account_id.to_string(), royalty.basis_points))
+                .collect::<Vec<_>>(),
+            vec![
+                ("c.near".to_string(), 3334),
+                ("a.near".to_string(), 3333),
+                ("b.near".to_string(), 3333),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_add_owner_share_royalties_exceed_total() {
+        let royalties = serde_json::from_value::<std::collections::HashMap<types::AccountId, u32>>(
+            serde_json::json!({ "artist.near": 15000, "platform.near": 5000 }),
+        )
+        .unwrap();
+        let royalties = add_owner_share(royalties, "owner.near").unwrap();
+        assert_eq!(
+            royalties
+                .iter()
+                .map(|royalty| (royalty.account_id.to_string(), royalty.basis_points))
+                .collect::<Vec<_>>(),
+            vec![
+                ("artist.near".to_string(), 7500),
+                ("platform.near".to_string(), 2500),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nft_royalties() {
+        let rpc_client = init_rpc();
+        let block = get_block();
+        let contract = near_primitives::types::AccountId::from_str("x.paras.near").unwrap();
+        let gateways = crate::config::NftGatewaysConfig::default();
+        let nft = super::super::nft_info::get_nft(
+            &rpc_client,
+            contract.clone(),
+            "415815:1".to_string(),
+            &block,
+            false,
+            &super::super::media::NftMediaContext {
+                base_uri: None,
+                gateways: &gateways,
+            },
+        )
+        .await
+        .unwrap();
+
+        let royalties = get_nft_royalties(&rpc_client, &contract, &nft, block.height)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            royalties
+                .iter()
+                .map(|royalty| royalty.basis_points)
+                .sum::<u32>(),
+            10_000
+        );
+    }
+}
@@ -4,6 +4,8 @@ mod data_provider;
 mod resources;
 mod schemas;
 
+pub(crate) use data_provider::{HttpReferenceFetcher, ReferenceResolver};
+
 pub(crate) fn register_services(app: &mut web::ServiceConfig) {
     app.service(
         web::resource("/accounts/{account_id}/NFT")
@@ -13,6 +15,19 @@ pub(crate) fn register_services(app: &mut web::ServiceConfig) {
         web::resource("/accounts/{account_id}/NFT/{contract_account_id}")
             .route(web::get().to(resources::get_nft_collection_by_contract)),
     )
+    .service(
+        web::resource("/NFT/{contract_account_id}")
+            .route(web::get().to(resources::get_nft_collection)),
+    )
+    // should go before `/NFT/{contract_account_id}/{token_id}`, otherwise `history`, `holders` are treated as token_id
+    .service(
+        web::resource("/NFT/{contract_account_id}/history")
+            .route(web::get().to(resources::get_nft_contract_history)),
+    )
+    .service(
+        web::resource("/NFT/{contract_account_id}/holders")
+            .route(web::get().to(resources::get_nft_holders)),
+    )
     .service(
         web::resource("/NFT/{contract_account_id}/{token_id}")
             .route(web::get().to(resources::get_nft)),
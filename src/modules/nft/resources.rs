@@ -3,10 +3,19 @@ use paperclip::actix::{
     web::{self, Json},
 };
 
-use crate::{db_helpers, modules, types};
+use crate::{config, db_helpers, errors, modules, types};
 
 use super::schemas;
 
+const NFT_DETAILS_ROYALTIES: &str = "royalties";
+const NFT_DETAILS_APPROVALS: &str = "approvals";
+const NFT_DETAILS_REFERENCE: &str = "reference";
+const NFT_DETAILS: [&str; 3] = [
+    NFT_DETAILS_ROYALTIES,
+    NFT_DETAILS_APPROVALS,
+    NFT_DETAILS_REFERENCE,
+];
+
 #[api_v2_operation(tags(NFT))]
 /// Get user's NFT collection overview
 ///
@@ -19,7 +28,7 @@ use super::schemas;
 ///   Full-featured pagination will be provided soon.
 pub async fn get_nft_collection_overview(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::NftCountsRequest>,
     limit_params: web::Query<types::query_params::LimitParams>,
@@ -48,71 +57,178 @@ pub async fn get_nft_collection_overview(
 ///
 /// This endpoint returns the list of NFTs with full details for the given `account_id`, NFT `contract_account_id`.
 /// You can use the `token_id` from this response and then request the NFT history for that token.
+/// Set `only_valid=true` to skip the NFTs which are not started or already expired (NEP-177).
 ///
 /// **Limitations**
 /// * We currently provide the most recent 100 items.
 ///   Full-featured pagination will be provided soon.
 pub async fn get_nft_collection_by_contract(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::NftCollectionRequest>,
     limit_params: web::Query<types::query_params::LimitParams>,
+    validity_params: web::Query<schemas::NftValidityParams>,
+    gateways: web::Data<config::NftGatewaysConfig>,
 ) -> crate::Result<Json<schemas::NftsResponse>> {
     let limit = types::query_params::checked_get_limit(limit_params.limit)?;
     let block = db_helpers::get_last_block(&pool_explorer).await?;
     modules::check_account_exists(&rpc_client, &request.account_id.0, block.height).await?;
 
+    let contract_metadata = super::data_provider::get_nft_contract_metadata(
+        &rpc_client,
+        request.contract_account_id.0.clone(),
+        block.height,
+    )
+    .await?;
+    let nfts = super::data_provider::get_nfts_by_contract(
+        &rpc_client,
+        request.contract_account_id.0.clone(),
+        request.account_id.0.clone(),
+        &block,
+        limit,
+        &super::data_provider::NftMediaContext {
+            base_uri: contract_metadata.base_uri.as_deref(),
+            gateways: &gateways,
+        },
+    )
+    .await?;
+
     Ok(Json(schemas::NftsResponse {
-        nfts: super::data_provider::get_nfts_by_contract(
-            &rpc_client,
-            request.contract_account_id.0.clone(),
-            request.account_id.0.clone(),
-            block.height,
-            limit,
-        )
-        .await?,
-        contract_metadata: super::data_provider::get_nft_contract_metadata(
-            &rpc_client,
-            request.contract_account_id.0.clone(),
-            block.height,
-        )
-        .await?,
+        nfts: filter_valid_nfts(nfts, &validity_params),
+        contract_metadata,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
     }))
 }
 
+#[api_v2_operation(tags(NFT))]
+/// Get NFT collection
+///
+/// This endpoint returns all the NFTs for the given NFT `contract_account_id`
+/// at the given `block_timestamp_nanos`/`block_height`.
+/// For the next page, use `next_from_index` from your previous response
+/// together with `block_height` from your previous response.
+///
+/// **Note:** We use the enumeration methods (NEP-181) of the contract if it supports them,
+/// the order of NFTs is defined by the contract in this case.
+/// Otherwise, we collect the NFTs from the Events NEP history, sorted by `token_id`.
+///
+/// Set `only_valid=true` to skip the NFTs which are not started or already expired (NEP-177).
+/// The filter is applied to the page, so the page could contain less than `limit` NFTs
+/// while `next_from_index` is provided.
+#[allow(clippy::too_many_arguments)]
+pub async fn get_nft_collection(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::NftContractRequest>,
+    block_params: web::Query<types::query_params::BlockParams>,
+    pagination_params: web::Query<schemas::NftTokensPaginationParams>,
+    validity_params: web::Query<schemas::NftValidityParams>,
+    gateways: web::Data<config::NftGatewaysConfig>,
+) -> crate::Result<Json<schemas::NftCollectionResponse>> {
+    let limit = types::query_params::checked_get_limit(pagination_params.limit)?;
+    let from_index = pagination_params.from_index.unwrap_or_default();
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
+
+    let contract_metadata = super::data_provider::get_nft_contract_metadata(
+        &rpc_client,
+        request.contract_account_id.0.clone(),
+        block.height,
+    )
+    .await?;
+    let (nfts, total_supply) = super::data_provider::get_nft_collection(
+        &pool_explorer,
+        &rpc_client,
+        &request.contract_account_id.0,
+        &block,
+        from_index,
+        limit,
+        &super::data_provider::NftMediaContext {
+            base_uri: contract_metadata.base_uri.as_deref(),
+            gateways: &gateways,
+        },
+    )
+    .await?;
+    let next_from_index = from_index + nfts.len() as u32;
+
+    Ok(Json(schemas::NftCollectionResponse {
+        next_from_index: if !nfts.is_empty() && (next_from_index as u128) < total_supply {
+            Some(next_from_index)
+        } else {
+            None
+        },
+        nfts: filter_valid_nfts(nfts, &validity_params),
+        total_supply: total_supply.into(),
+        contract_metadata,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+        finality: block.finality,
+    }))
+}
+
 #[api_v2_operation(tags(NFT))]
 /// Get NFT
 ///
 /// This endpoint returns detailed information on the NFT
 /// for the given `token_id`, NFT `contract_account_id`, `block_timestamp_nanos`/`block_height`.
+/// Set `include=royalties` to get the royalty splits (NEP-199) of the NFT,
+/// `include=approvals` to get the accounts approved to transfer the NFT (NEP-178),
+/// `include=reference` to get the off-chain JSON from `reference`, verified by `reference_hash`.
+#[allow(clippy::too_many_arguments)]
 pub async fn get_nft(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::NftRequest>,
     block_params: web::Query<types::query_params::BlockParams>,
+    details_params: web::Query<schemas::NftDetailsParams>,
+    reference_resolver: web::Data<super::ReferenceResolver>,
+    gateways: web::Data<config::NftGatewaysConfig>,
 ) -> crate::Result<Json<schemas::NftResponse>> {
-    let block = db_helpers::checked_get_block(&pool_explorer, &block_params).await?;
+    let include = checked_get_nft_details(&details_params)?;
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
 
-    Ok(Json(schemas::NftResponse {
-        nft: super::data_provider::get_nft(
+    let contract_metadata = super::data_provider::get_nft_contract_metadata(
+        &rpc_client,
+        request.contract_account_id.0.clone(),
+        block.height,
+    )
+    .await?;
+    let mut nft = super::data_provider::get_nft(
+        &rpc_client,
+        request.contract_account_id.0.clone(),
+        request.token_id.clone(),
+        &block,
+        include.contains(&NFT_DETAILS_APPROVALS),
+        &super::data_provider::NftMediaContext {
+            base_uri: contract_metadata.base_uri.as_deref(),
+            gateways: &gateways,
+        },
+    )
+    .await?;
+    if include.contains(&NFT_DETAILS_ROYALTIES) {
+        nft.royalties = super::data_provider::get_nft_royalties(
             &rpc_client,
-            request.contract_account_id.0.clone(),
-            request.token_id.clone(),
-            block.height,
-        )
-        .await?,
-        contract_metadata: super::data_provider::get_nft_contract_metadata(
-            &rpc_client,
-            request.contract_account_id.0.clone(),
+            &request.contract_account_id.0,
+            &nft,
             block.height,
         )
-        .await?,
+        .await?;
+    }
+    if include.contains(&NFT_DETAILS_REFERENCE) {
+        nft.resolved_reference = reference_resolver
+            .resolve(&nft.metadata, contract_metadata.base_uri.as_deref())
+            .await;
+    }
+
+    Ok(Json(schemas::NftResponse {
+        nft,
+        contract_metadata,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
+        finality: block.finality,
     }))
 }
 
@@ -122,20 +238,51 @@ pub async fn get_nft(
 /// This endpoint returns the transaction history for the given NFT.
 /// **Note:** The result is centered around the history of the specific NFT and will return list of its passing owners.
 ///
+/// Set `skip_transaction_info` if you don't need the transaction for each item, it makes the response faster.
+///
 /// **Limitations**
 /// * For now, we only support NFT contracts that implement the Events NEP standard.
+/// * The items do not have `event_index` yet, so `with_event_index_parts` is not supported.
 /// * We currently provide the most recent 100 items.
 ///   Full-featured pagination will be provided soon.
 pub async fn get_nft_history(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::NftRequest>,
     limit_params: web::Query<types::query_params::LimitParams>,
+    history_params: web::Query<types::query_params::HistoryParams>,
+    gateways: web::Data<config::NftGatewaysConfig>,
 ) -> crate::Result<Json<schemas::NftHistoryResponse>> {
     let limit = types::query_params::checked_get_limit(limit_params.limit)?;
+    if history_params.with_event_index_parts.unwrap_or_default() {
+        return Err(errors::ErrorKind::InvalidInput(
+            "with_event_index_parts is not supported for NFT history yet".to_string(),
+        )
+        .into());
+    }
     let block = db_helpers::get_last_block(&pool_explorer).await?;
 
+    // We need only base_uri here, so the contracts without metadata are fine
+    let base_uri = super::data_provider::get_nft_base_uri(
+        &rpc_client,
+        &request.contract_account_id.0,
+        block.height,
+    )
+    .await?;
+    let nft = super::data_provider::get_nft(
+        &rpc_client,
+        request.contract_account_id.0.clone(),
+        request.token_id.clone(),
+        &block,
+        false,
+        &super::data_provider::NftMediaContext {
+            base_uri: base_uri.as_deref(),
+            gateways: &gateways,
+        },
+    )
+    .await?;
+
     Ok(Json(schemas::NftHistoryResponse {
         history: super::data_provider::get_nft_history(
             &pool_explorer,
@@ -143,20 +290,101 @@ pub async fn get_nft_history(
             &request.token_id,
             &block,
             limit,
+            !history_params.skip_transaction_info.unwrap_or_default(),
         )
         .await?,
-        nft: super::data_provider::get_nft(
-            &rpc_client,
-            request.contract_account_id.0.clone(),
-            request.token_id.clone(),
-            block.height,
-        )
-        .await?,
+        nft,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+    }))
+}
+
+#[api_v2_operation(tags(NFT))]
+/// Get NFT contract history
+///
+/// This endpoint returns the history of all the NFT movements (mint, transfer, burn)
+/// for the given NFT `contract_account_id`.
+/// Use `cause` and `account_id` to filter the events.
+/// For the next page, use `event_index` of the last item in your previous response.
+/// Set `with_event_index_parts` to get the decoded `event_index` for each item.
+/// Set `skip_transaction_info` if you don't need the transaction for each item, it makes the response faster.
+///
+/// **Limitations**
+/// For now, we only support NFT contracts that implement the Events NEP standard.
+pub async fn get_nft_contract_history(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::NftContractRequest>,
+    pagination_params: web::Query<types::query_params::PaginationParams>,
+    filter_params: web::Query<schemas::NftHistoryFilterParams>,
+    history_params: web::Query<types::query_params::HistoryParams>,
+) -> crate::Result<Json<schemas::NftContractHistoryResponse>> {
+    let pagination = modules::checked_get_pagination_params(&pagination_params).await?;
+    let block = db_helpers::get_block_from_pagination(&pool_explorer, &pagination).await?;
+
+    let mut history = super::data_provider::get_nft_contract_history(
+        &pool_explorer,
+        &request.contract_account_id.0,
+        &block,
+        &pagination,
+        &filter_params,
+        !history_params.skip_transaction_info.unwrap_or_default(),
+    )
+    .await?;
+    if history_params.with_event_index_parts.unwrap_or_default() {
+        for item in history.iter_mut() {
+            item.event_index_parts =
+                Some(types::EventIndex::new(item.event_index.0).shard_event_parts());
+        }
+    }
+
+    Ok(Json(schemas::NftContractHistoryResponse {
+        history,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
     }))
 }
 
+#[api_v2_operation(tags(NFT))]
+/// Get NFT holders
+///
+/// This endpoint returns the owners of the NFTs for the given NFT `contract_account_id`
+/// and the number of NFTs each of them holds at the given `block_timestamp_nanos`/`block_height`.
+/// The holders are sorted by the number of NFTs, `total_holders` shows the number of unique holders.
+/// For the next page, use `account_id` of the last holder in your previous response
+/// together with `block_height` from your previous response.
+///
+/// **Limitations**
+/// For now, we only support NFT contracts that implement the Events NEP standard.
+pub async fn get_nft_holders(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::NftContractRequest>,
+    block_params: web::Query<types::query_params::BlockParams>,
+    pagination_params: web::Query<schemas::NftHoldersPaginationParams>,
+) -> crate::Result<Json<schemas::NftHoldersResponse>> {
+    let limit = types::query_params::checked_get_limit(pagination_params.limit)?;
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
+
+    let (holders, total_holders) = super::data_provider::get_nft_holders(
+        &pool_explorer,
+        &request.contract_account_id.0,
+        &block,
+        limit,
+        pagination_params.after_account_id.as_ref(),
+    )
+    .await?;
+
+    Ok(Json(schemas::NftHoldersResponse {
+        holders,
+        total_holders,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+        finality: block.finality,
+    }))
+}
+
 #[api_v2_operation(tags(NFT))]
 /// Get NFT contract metadata
 ///
@@ -164,12 +392,12 @@ pub async fn get_nft_history(
 /// **Note:** This is contract-wide metadata. Each NFT also has its own metadata.
 pub async fn get_nft_contract_metadata(
     pool_explorer: web::Data<db_helpers::ExplorerPool>,
-    rpc_client: web::Data<near_jsonrpc_client::JsonRpcClient>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
     _: crate::types::pagoda_api_key::PagodaApiKey,
     request: actix_web_validator::Path<schemas::MetadataRequest>,
     block_params: web::Query<types::query_params::BlockParams>,
 ) -> crate::Result<Json<schemas::MetadataResponse>> {
-    let block = db_helpers::checked_get_block(&pool_explorer, &block_params).await?;
+    let block = db_helpers::checked_get_block(&pool_explorer, &rpc_client, &block_params).await?;
 
     Ok(Json(schemas::MetadataResponse {
         metadata: super::data_provider::get_nft_contract_metadata(
@@ -180,5 +408,39 @@ pub async fn get_nft_contract_metadata(
         .await?,
         block_timestamp_nanos: types::U64::from(block.timestamp),
         block_height: types::U64::from(block.height),
+        finality: block.finality,
     }))
 }
+
+fn filter_valid_nfts(
+    nfts: Vec<schemas::Nft>,
+    params: &schemas::NftValidityParams,
+) -> Vec<schemas::Nft> {
+    if params.only_valid.unwrap_or_default() {
+        nfts.into_iter()
+            .filter(|nft| nft.validity.is_valid)
+            .collect()
+    } else {
+        nfts
+    }
+}
+
+/// Validates `include` received from the user
+fn checked_get_nft_details(params: &schemas::NftDetailsParams) -> crate::Result<Vec<&str>> {
+    let mut result = vec![];
+    if let Some(include) = &params.include {
+        for item in include.split(',').map(|item| item.trim()) {
+            match NFT_DETAILS.iter().find(|detail| **detail == item) {
+                Some(detail) => result.push(*detail),
+                None => {
+                    return Err(errors::ErrorKind::InvalidInput(format!(
+                        "include should contain only the values from {:?}",
+                        NFT_DETAILS
+                    ))
+                    .into())
+                }
+            }
+        }
+    }
+    Ok(result)
+}
@@ -32,6 +32,14 @@ pub struct MetadataRequest {
     pub contract_account_id: types::AccountId,
 }
 
+#[derive(
+    Validate, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema,
+)]
+pub struct NftContractRequest {
+    #[validate(custom = "crate::errors::validate_account_id")]
+    pub contract_account_id: types::AccountId,
+}
+
 /// `token_id` is available at `NftCollectionByContractResponse`
 #[derive(
     Validate, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema,
@@ -42,6 +50,42 @@ pub struct NftRequest {
     pub token_id: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftHistoryFilterParams {
+    /// One of ["MINT", "TRANSFER", "BURN"], case-insensitive
+    pub cause: Option<String>,
+    /// Show only the events where the given account is the old or the new owner
+    pub account_id: Option<types::AccountId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftHoldersPaginationParams {
+    /// Maximum available limit is `limits.max_page_limit` from the server config (100 by default)
+    pub limit: Option<u32>,
+    /// For the next page, use `account_id` of the last holder in your previous response
+    pub after_account_id: Option<types::AccountId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftTokensPaginationParams {
+    /// Maximum available limit is `limits.max_page_limit` from the server config (100 by default)
+    pub limit: Option<u32>,
+    /// For the next page, use `next_from_index` from your previous response
+    pub from_index: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftDetailsParams {
+    /// Comma-separated list of the additional NFT details. Available values: ["royalties", "approvals", "reference"]
+    pub include: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftValidityParams {
+    /// Set `only_valid=true` to get only the NFTs valid at the given block (see NEP-177 `starts_at`, `expires_at`)
+    pub only_valid: Option<bool>,
+}
+
 // *** Responses ***
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
@@ -59,12 +103,25 @@ pub struct NftsResponse {
     pub block_height: types::U64,
 }
 
+/// `next_from_index` is empty if there are no more NFTs in the collection
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftCollectionResponse {
+    pub nfts: Vec<Nft>,
+    pub total_supply: types::U128,
+    pub next_from_index: Option<u32>,
+    pub contract_metadata: NftContractMetadata,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
 pub struct NftResponse {
     pub nft: Nft,
     pub contract_metadata: NftContractMetadata,
     pub block_timestamp_nanos: types::U64,
     pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
@@ -75,11 +132,28 @@ pub struct NftHistoryResponse {
     pub block_height: types::U64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftContractHistoryResponse {
+    pub history: Vec<ContractHistoryItem>,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftHoldersResponse {
+    pub holders: Vec<NftHolder>,
+    pub total_holders: u64,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
 pub struct MetadataResponse {
     pub metadata: NftContractMetadata,
     pub block_timestamp_nanos: types::U64,
     pub block_height: types::U64,
+    pub finality: types::query_params::Finality,
 }
 
 // ---
@@ -97,6 +171,36 @@ pub struct HistoryItem {
     pub status: String,
     pub block_timestamp_nanos: types::U64,
     pub block_height: types::U64,
+    /// Provided unless `skip_transaction_info` is set
+    pub receipt_id: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub signer_account_id: Option<types::AccountId>,
+}
+
+/// This type describes the history of NFT movements for the whole NFT contract.
+/// `cause` is one of ["MINT", "TRANSFER", "BURN"]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct ContractHistoryItem {
+    pub event_index: types::U128,
+    pub token_id: String,
+    pub cause: String,
+    pub old_account_id: Option<types::AccountId>,
+    pub new_account_id: Option<types::AccountId>,
+    pub status: String,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    /// Provided if `with_event_index_parts` is set
+    pub event_index_parts: Option<types::EventIndexParts>,
+    /// Provided unless `skip_transaction_info` is set
+    pub receipt_id: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub signer_account_id: Option<types::AccountId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftHolder {
+    pub account_id: types::AccountId,
+    pub nft_count: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
@@ -128,8 +232,61 @@ pub struct Nft {
     pub token_id: String,
     pub owner_account_id: String,
     pub metadata: NftMetadata,
-    // TODO PHASE 1 do we want to show them? People often put here weird things
-    // pub approved_account_ids: Option<std::collections::HashMap<AccountId, u64>>,
+    /// Absolute HTTP(S) URL of `media`. IPFS and Arweave links go through the gateway,
+    /// relative paths are resolved with `base_uri` of the contract
+    pub media_url: Option<String>,
+    /// `VERIFIED`, `MISMATCH` if `media` is IPFS CID of the raw content, so that `media_hash` could be checked by the CID;
+    /// `UNVERIFIED` for other links (we do not load the media); `NOT_PROVIDED` if there is no `media_hash`
+    pub media_hash_status: Option<String>,
+    pub validity: NftValidity,
+    /// Provided if `include` contains `approvals` and the contract implements NEP-178.
+    /// Sorted by `approval_id` descending, at most 100 approvals are shown
+    pub approvals: Option<Vec<NftApproval>>,
+    /// Provided if `include` contains `royalties` and the contract implements NEP-199
+    pub royalties: Option<Vec<NftRoyalty>>,
+    /// Provided if `include` contains `reference` and the NFT metadata has `reference`
+    pub resolved_reference: Option<NftResolvedReference>,
+}
+
+/// The share of the NFT sale price the account receives, in basis points (1/100 of percent).
+/// Inspired by https://nomicon.io/Standards/Tokens/NonFungibleToken/Payout
+/// The owner's share is also included, so all the shares sum up to 10000
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftRoyalty {
+    pub account_id: types::AccountId,
+    pub basis_points: u32,
+}
+
+/// NEP-177 timestamps from the NFT metadata, normalized to unix nanoseconds.
+/// We support ISO 8601 datetimes and unix milliseconds, the timestamps in other formats are null
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftValidity {
+    pub issued_at_nanos: Option<types::U64>,
+    pub starts_at_nanos: Option<types::U64>,
+    pub expires_at_nanos: Option<types::U64>,
+    pub updated_at_nanos: Option<types::U64>,
+    /// `starts_at` <= block timestamp < `expires_at`, the missing bounds are not checked
+    pub is_valid: bool,
+}
+
+/// Off-chain JSON document referenced by `reference` in the NFT metadata.
+/// `hash_status` is one of `VERIFIED`, `MISMATCH`, `NOT_PROVIDED` (the metadata has no `reference_hash`),
+/// `NOT_CHECKED` (we could not load the document, see `error`).
+/// The document is not shown if it does not match `reference_hash`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftResolvedReference {
+    pub url: String,
+    pub hash_status: String,
+    pub document: Option<types::JsonValue>,
+    pub error: Option<String>,
+}
+
+/// The account approved to transfer the NFT on behalf of the owner.
+/// Inspired by https://nomicon.io/Standards/Tokens/NonFungibleToken/ApprovalManagement
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftApproval {
+    pub account_id: types::AccountId,
+    pub approval_id: types::U64,
 }
 
 /// The type for Non Fungible Token Metadata. Inspired by
@@ -141,10 +298,26 @@ pub struct NftMetadata {
     pub media: Option<String>, // URL to associated media, preferably to decentralized, content-addressed data_provider
     pub media_hash: Option<String>, // Base64-encoded sha256 hash of content referenced by the `media` field. Required if `media` is included.
     pub copies: Option<u64>, // number of copies of this set of metadata in existence when token was minted.
-    //pub issued_at: Option<String>, // ISO 8601 datetime when token was issued or minted
-    //pub expires_at: Option<String>, // ISO 8601 datetime when token expires
-    //pub starts_at: Option<String>, // ISO 8601 datetime when token starts being valid
-    //pub updated_at: Option<String>, // ISO 8601 datetime when token was last updated
+    #[serde(
+        default,
+        deserialize_with = "types::timestamp::deserialize_string_or_number"
+    )]
+    pub issued_at: Option<String>, // ISO 8601 datetime when token was issued or minted
+    #[serde(
+        default,
+        deserialize_with = "types::timestamp::deserialize_string_or_number"
+    )]
+    pub expires_at: Option<String>, // ISO 8601 datetime when token expires
+    #[serde(
+        default,
+        deserialize_with = "types::timestamp::deserialize_string_or_number"
+    )]
+    pub starts_at: Option<String>, // ISO 8601 datetime when token starts being valid
+    #[serde(
+        default,
+        deserialize_with = "types::timestamp::deserialize_string_or_number"
+    )]
+    pub updated_at: Option<String>, // ISO 8601 datetime when token was last updated
     pub extra: Option<String>, // anything extra the NFT wants to data_provider on-chain. Can be stringified JSON.
     pub reference: Option<String>, // URL to an off-chain JSON file with more info.
     pub reference_hash: Option<String>, // Base64-encoded sha256 hash of JSON from reference field. Required if `reference` is included.
@@ -0,0 +1,4 @@
+mod models;
+mod status;
+
+pub(crate) use status::{get_status, watch_indexer_lag, with_indexer_lag_header};
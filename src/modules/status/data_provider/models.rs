@@ -0,0 +1,7 @@
+use crate::BigDecimal;
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct LatestEvent {
+    pub block_height: BigDecimal,
+    pub block_timestamp: BigDecimal,
+}
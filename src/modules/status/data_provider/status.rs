@@ -0,0 +1,214 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use actix_web::{dev, http::header};
+
+use crate::modules::status;
+use crate::{config, db_helpers, errors, rpc_helpers, types};
+
+const STATUS_OK: &str = "OK";
+const STATUS_DEGRADED: &str = "DEGRADED";
+const NANOS_IN_SECOND: u64 = 1_000_000_000;
+const INDEXER_LAG_HEADER: &str = "x-indexer-lag-seconds";
+
+// u64::MAX means we have not measured the lag yet
+static INDEXER_LAG_SECONDS: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Never fails: if some component is unavailable, the status is `DEGRADED` with the error inside
+pub(crate) async fn get_status(
+    pool_explorer: &db_helpers::ExplorerPool,
+    pool_balances: &db_helpers::BalancesPool,
+    rpc_client: &crate::rpc_pool::RpcPool,
+    status_config: &config::StatusConfig,
+) -> status::schemas::StatusResponse {
+    let (rpc_head, explorer_block, balances_block) = futures::future::join3(
+        get_rpc_head(rpc_client),
+        db_helpers::get_last_block(pool_explorer),
+        get_last_balances_block(pool_balances),
+    )
+    .await;
+
+    let mut errors = vec![];
+    let rpc_head = collect_error("rpc", rpc_head, &mut errors);
+    let explorer_db = collect_error("explorer_db", explorer_block, &mut errors)
+        .map(|block| get_indexer_status(&block, rpc_head.as_ref()));
+    let balances_db = collect_error("balances_db", balances_block, &mut errors)
+        .map(|block| get_indexer_status(&block, rpc_head.as_ref()));
+
+    // The lag is unknown if we could not reach any of the components
+    let lag_seconds = match (&explorer_db, &balances_db) {
+        (Some(explorer_db), Some(balances_db)) => explorer_db
+            .lag_seconds
+            .zip(balances_db.lag_seconds)
+            .map(|(explorer_lag, balances_lag)| explorer_lag.max(balances_lag)),
+        _ => None,
+    };
+    INDEXER_LAG_SECONDS.store(lag_seconds.unwrap_or(u64::MAX), Ordering::Relaxed);
+    status::schemas::StatusResponse {
+        status: if lag_seconds.map_or(false, |lag_seconds| {
+            lag_seconds <= status_config.max_lag_seconds
+        }) {
+            STATUS_OK
+        } else {
+            STATUS_DEGRADED
+        }
+        .to_string(),
+        rpc: rpc_head.map(|rpc_head| status::schemas::BlockStatus {
+            block_timestamp_nanos: rpc_head.timestamp.into(),
+            block_height: rpc_head.height.into(),
+        }),
+        explorer_db,
+        balances_db,
+        lag_seconds,
+        max_lag_seconds: status_config.max_lag_seconds,
+        errors,
+    }
+}
+
+/// Refreshes the lag for `x-indexer-lag-seconds` header, never returns.
+/// The lag becomes unknown (the header is not given) while any component is unavailable
+pub(crate) async fn watch_indexer_lag(
+    pool_explorer: db_helpers::ExplorerPool,
+    pool_balances: db_helpers::BalancesPool,
+    rpc_client: std::sync::Arc<crate::rpc_pool::RpcPool>,
+    status_config: config::StatusConfig,
+) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(
+        status_config.refresh_interval_ms,
+    ));
+    loop {
+        interval.tick().await;
+        let status = get_status(&pool_explorer, &pool_balances, &rpc_client, &status_config).await;
+        for error in status.errors {
+            tracing::warn!(
+                target: crate::LOGGER_MSG,
+                "Failed to refresh the indexer lag, {} is unavailable: {}",
+                error.component,
+                error.message
+            );
+        }
+    }
+}
+
+/// Adds `x-indexer-lag-seconds` header to the response, if we already know the lag
+pub(crate) async fn with_indexer_lag_header<B, F>(
+    response: F,
+) -> Result<dev::ServiceResponse<B>, actix_web::Error>
+where
+    F: std::future::Future<Output = Result<dev::ServiceResponse<B>, actix_web::Error>>,
+{
+    let mut response = response.await?;
+    let lag_seconds = INDEXER_LAG_SECONDS.load(Ordering::Relaxed);
+    if lag_seconds != u64::MAX {
+        response.headers_mut().insert(
+            header::HeaderName::from_static(INDEXER_LAG_HEADER),
+            header::HeaderValue::from(lag_seconds),
+        );
+    }
+    Ok(response)
+}
+
+async fn get_rpc_head(rpc_client: &crate::rpc_pool::RpcPool) -> crate::Result<db_helpers::Block> {
+    let rpc_head =
+        rpc_helpers::get_block_header(rpc_client, near_primitives::types::Finality::None).await?;
+    Ok(db_helpers::Block {
+        timestamp: rpc_head.timestamp,
+        height: rpc_head.height,
+        finality: types::query_params::Finality::Optimistic,
+    })
+}
+
+fn collect_error<T>(
+    component: &str,
+    result: crate::Result<T>,
+    errors: &mut Vec<status::schemas::ComponentError>,
+) -> Option<T> {
+    result
+        .map_err(|err| {
+            errors.push(status::schemas::ComponentError {
+                component: component.to_string(),
+                message: err.message,
+            })
+        })
+        .ok()
+}
+
+async fn get_last_balances_block(
+    db_helpers::BalancesPool(pool_balances): &db_helpers::BalancesPool,
+) -> crate::Result<db_helpers::Block> {
+    match db_helpers::select_retry_or_panic::<super::models::LatestEvent>(
+        pool_balances,
+        r"SELECT block_height, block_timestamp
+          FROM near_balance_events
+          ORDER BY event_index DESC
+          LIMIT 1",
+        &[],
+    )
+    .await?
+    .first()
+    {
+        None => {
+            Err(errors::ErrorKind::DBError("near_balance_events table is empty".to_string()).into())
+        }
+        Some(event) => Ok(db_helpers::Block {
+            timestamp: types::numeric::to_u64(&event.block_timestamp)?,
+            height: types::numeric::to_u64(&event.block_height)?,
+            finality: types::query_params::Finality::Optimistic,
+        }),
+    }
+}
+
+fn get_indexer_status(
+    block: &db_helpers::Block,
+    rpc_head: Option<&db_helpers::Block>,
+) -> status::schemas::IndexerStatus {
+    status::schemas::IndexerStatus {
+        block_timestamp_nanos: block.timestamp.into(),
+        block_height: block.height.into(),
+        lag_blocks: rpc_head.map(|rpc_head| rpc_head.height.saturating_sub(block.height).into()),
+        lag_seconds: rpc_head
+            .map(|rpc_head| rpc_head.timestamp.saturating_sub(block.timestamp) / NANOS_IN_SECOND),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+
+    #[test]
+    fn test_indexer_status() {
+        let rpc_head = get_block();
+        let block = db_helpers::Block {
+            timestamp: rpc_head.timestamp - 90 * NANOS_IN_SECOND,
+            height: rpc_head.height - 75,
+            finality: types::query_params::Finality::Final,
+        };
+        let indexer_status = get_indexer_status(&block, Some(&rpc_head));
+        assert_eq!(indexer_status.lag_blocks, Some(75.into()));
+        assert_eq!(indexer_status.lag_seconds, Some(90));
+
+        // The indexer could be ahead of the RPC node we use
+        let indexer_status = get_indexer_status(&rpc_head, Some(&block));
+        assert_eq!(indexer_status.lag_blocks, Some(0.into()));
+        assert_eq!(indexer_status.lag_seconds, Some(0));
+
+        // RPC is unavailable, the lag is unknown
+        let indexer_status = get_indexer_status(&block, None);
+        assert_eq!(indexer_status.block_height, block.height.into());
+        assert_eq!(indexer_status.lag_blocks, None);
+        assert_eq!(indexer_status.lag_seconds, None);
+    }
+
+    #[test]
+    fn test_collect_error() {
+        let mut errors = vec![];
+        assert_eq!(collect_error("rpc", Ok(1), &mut errors), Some(1));
+        assert!(errors.is_empty());
+
+        let result: crate::Result<u64> =
+            Err(errors::ErrorKind::RPCError("connection refused".to_string()).into());
+        assert_eq!(collect_error("rpc", result, &mut errors), None);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].component, "rpc");
+    }
+}
@@ -0,0 +1,11 @@
+use paperclip::actix::web;
+
+mod data_provider;
+mod resources;
+mod schemas;
+
+pub(crate) use data_provider::{watch_indexer_lag, with_indexer_lag_header};
+
+pub(crate) fn register_services(app: &mut web::ServiceConfig) {
+    app.service(web::resource("/status").route(web::get().to(resources::get_status)));
+}
@@ -0,0 +1,26 @@
+use paperclip::actix::{
+    api_v2_operation,
+    web::{self, Json},
+};
+
+use super::{data_provider, schemas};
+use crate::{config, db_helpers};
+
+#[api_v2_operation(tags(Status))]
+/// Get indexer status
+///
+/// This endpoint returns the latest block we have in each of our databases,
+/// the latest block known by RPC, and how far the databases are behind it.
+/// The service is considered to be degraded if the lag is bigger than `max_lag_seconds`
+/// or if any of the components is unavailable, see `errors` for the details.
+pub async fn get_status(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    pool_balances: web::Data<db_helpers::BalancesPool>,
+    rpc_client: web::Data<crate::rpc_pool::RpcPool>,
+    status_config: web::Data<config::StatusConfig>,
+) -> crate::Result<Json<schemas::StatusResponse>> {
+    Ok(Json(
+        data_provider::get_status(&pool_explorer, &pool_balances, &rpc_client, &status_config)
+            .await,
+    ))
+}
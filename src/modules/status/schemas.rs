@@ -0,0 +1,45 @@
+use paperclip::actix::Apiv2Schema;
+
+use crate::types;
+
+// *** Responses ***
+
+/// `status` is `DEGRADED` if any of the indexers is behind RPC for more than `max_lag_seconds`,
+/// or if any of the components is unavailable. The unavailable components are listed in `errors`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct StatusResponse {
+    pub status: String,
+    pub rpc: Option<BlockStatus>,
+    pub explorer_db: Option<IndexerStatus>,
+    pub balances_db: Option<IndexerStatus>,
+    /// The lag of the most outdated indexer, we give it in `x-indexer-lag-seconds` header.
+    /// Not provided if any of the components is unavailable
+    pub lag_seconds: Option<u64>,
+    pub max_lag_seconds: u64,
+    pub errors: Vec<ComponentError>,
+}
+
+// ---
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct BlockStatus {
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
+
+/// The latest block the indexer has written, and how far it is from the RPC head.
+/// The lag is not provided if RPC is unavailable
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct IndexerStatus {
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+    pub lag_blocks: Option<types::U64>,
+    pub lag_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct ComponentError {
+    /// `rpc`, `explorer_db` or `balances_db`
+    pub component: String,
+    pub message: String,
+}
@@ -0,0 +1,177 @@
+use crate::modules::transactions;
+use crate::{db_helpers, errors};
+
+pub(crate) async fn get_actions_by_transaction(
+    pool_explorer: &sqlx::Pool<sqlx::Postgres>,
+    transaction_hashes: &[String],
+) -> crate::Result<std::collections::HashMap<String, Vec<transactions::schemas::Action>>> {
+    get_actions(
+        pool_explorer,
+        r"
+            SELECT
+                transaction_hash id,
+                action_kind::text kind,
+                args
+            FROM transaction_actions
+            WHERE transaction_hash = ANY($1::text[])
+            ORDER BY transaction_hash, index_in_transaction
+        ",
+        transaction_hashes,
+    )
+    .await
+}
+
+pub(crate) async fn get_actions_by_receipt(
+    pool_explorer: &sqlx::Pool<sqlx::Postgres>,
+    receipt_ids: &[String],
+) -> crate::Result<std::collections::HashMap<String, Vec<transactions::schemas::Action>>> {
+    get_actions(
+        pool_explorer,
+        r"
+            SELECT
+                receipt_id id,
+                action_kind::text kind,
+                args
+            FROM action_receipt_actions
+            WHERE receipt_id = ANY($1::text[])
+            ORDER BY receipt_id, index_in_action_receipt
+        ",
+        receipt_ids,
+    )
+    .await
+}
+
+async fn get_actions(
+    pool_explorer: &sqlx::Pool<sqlx::Postgres>,
+    query: &str,
+    ids: &[String],
+) -> crate::Result<std::collections::HashMap<String, Vec<transactions::schemas::Action>>> {
+    let mut result: std::collections::HashMap<String, Vec<transactions::schemas::Action>> =
+        std::collections::HashMap::new();
+    if ids.is_empty() {
+        return Ok(result);
+    }
+
+    let actions = db_helpers::select_retry_or_panic::<super::models::ActionInfo>(
+        pool_explorer,
+        query,
+        &[db_helpers::to_sql_array(ids)],
+    )
+    .await?;
+    for action in actions {
+        result
+            .entry(action.id)
+            .or_default()
+            .push(transactions::schemas::Action {
+                args: decode_args(&action.kind, action.args).into(),
+                kind: action.kind,
+            });
+    }
+    Ok(result)
+}
+
+// The DB keeps FunctionCall args in base64 since they are not obliged to be JSON.
+// Most of the contracts use JSON, so we add the parsed version next to the original one
+fn decode_args(kind: &str, mut args: serde_json::Value) -> serde_json::Value {
+    if kind != "FUNCTION_CALL" || args.get("args_json").is_some() {
+        return args;
+    }
+    let args_json = args
+        .get("args_base64")
+        .and_then(|value| value.as_str())
+        .and_then(|value| base64::decode(value).ok())
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok());
+    if let (Some(args_json), Some(object)) = (args_json, args.as_object_mut()) {
+        object.insert("args_json".to_string(), args_json);
+    }
+    args
+}
+
+// Only TRANSFER and FUNCTION_CALL actions have the deposit
+pub(crate) fn get_deposit(actions: &[transactions::schemas::Action]) -> crate::Result<u128> {
+    let mut deposit: u128 = 0;
+    for action in actions {
+        if let Some(value) = action
+            .args
+            .0
+            .get("deposit")
+            .and_then(|value| value.as_str())
+        {
+            let action_deposit = value.parse::<u128>().map_err(|e| {
+                errors::ErrorKind::InternalError(format!(
+                    "Failed to parse deposit {} from action args: {}",
+                    value, e
+                ))
+            })?;
+            deposit = deposit.checked_add(action_deposit).ok_or_else(|| {
+                errors::ErrorKind::InternalError(format!(
+                    "Deposit overflow while adding {} to {}",
+                    action_deposit, deposit
+                ))
+            })?;
+        }
+    }
+    Ok(deposit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types;
+
+    #[test]
+    fn test_decode_function_call_args() {
+        let args = serde_json::json!({
+            "method_name": "ft_transfer",
+            "args_base64": base64::encode(r#"{"receiver_id":"olga.near","amount":"1"}"#),
+            "gas": 30000000000000_u64,
+            "deposit": "1",
+        });
+        let decoded = decode_args("FUNCTION_CALL", args);
+        assert_eq!(
+            decoded["args_json"],
+            serde_json::json!({"receiver_id": "olga.near", "amount": "1"})
+        );
+    }
+
+    #[test]
+    fn test_decode_function_call_args_not_json() {
+        let args = serde_json::json!({
+            "method_name": "submit",
+            "args_base64": base64::encode([0_u8, 1, 2, 3]),
+            "gas": 30000000000000_u64,
+            "deposit": "0",
+        });
+        let decoded = decode_args("FUNCTION_CALL", args.clone());
+        assert_eq!(decoded, args);
+    }
+
+    #[test]
+    fn test_deposit() {
+        let actions = vec![
+            transactions::schemas::Action {
+                kind: "TRANSFER".to_string(),
+                args: types::JsonValue(serde_json::json!({"deposit": "10"})),
+            },
+            transactions::schemas::Action {
+                kind: "ADD_KEY".to_string(),
+                args: types::JsonValue(serde_json::json!({"public_key": "ed25519:..."})),
+            },
+            transactions::schemas::Action {
+                kind: "FUNCTION_CALL".to_string(),
+                args: types::JsonValue(serde_json::json!({"deposit": "5"})),
+            },
+        ];
+        assert_eq!(get_deposit(&actions).unwrap(), 15);
+    }
+
+    #[test]
+    fn test_deposit_overflow() {
+        let action = transactions::schemas::Action {
+            kind: "TRANSFER".to_string(),
+            args: types::JsonValue(serde_json::json!({ "deposit": u128::MAX.to_string() })),
+        };
+        let actions = vec![action.clone(), action];
+        assert_eq!(get_deposit(&actions).unwrap_err().code, 500);
+    }
+}
@@ -0,0 +1,7 @@
+mod actions;
+mod models;
+mod transaction_info;
+mod transactions;
+
+pub(crate) use transaction_info::get_transaction;
+pub(crate) use transactions::get_account_transactions;
@@ -0,0 +1,66 @@
+use crate::BigDecimal;
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct TransactionInfo {
+    pub transaction_hash: String,
+    pub signer_account_id: String,
+    pub receiver_account_id: String,
+    pub status: String,
+    pub gas_burnt: BigDecimal,
+    pub shard_id: BigDecimal,
+    pub index_in_chunk: i32,
+    pub block_timestamp_nanos: BigDecimal,
+    pub block_height: BigDecimal,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct ActionInfo {
+    pub id: String,
+    pub kind: String,
+    pub args: serde_json::Value,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct ReceiptInfo {
+    pub receipt_id: String,
+    pub parent_receipt_id: Option<String>,
+    pub predecessor_account_id: String,
+    pub receiver_account_id: String,
+    pub kind: String,
+    pub status: String,
+    pub gas_burnt: BigDecimal,
+    pub tokens_burnt: BigDecimal,
+    pub block_timestamp_nanos: BigDecimal,
+    pub block_height: BigDecimal,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct NearBalanceChangeInfo {
+    pub receipt_id: String,
+    pub affected_account_id: String,
+    pub involved_account_id: Option<String>,
+    pub delta_balance: BigDecimal,
+    pub cause: String,
+    pub status: String,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct FtBalanceChangeInfo {
+    pub receipt_id: String,
+    pub contract_account_id: String,
+    pub affected_account_id: String,
+    pub involved_account_id: Option<String>,
+    pub delta_balance: BigDecimal,
+    pub cause: String,
+    pub status: String,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct NftChangeInfo {
+    pub receipt_id: String,
+    pub contract_account_id: String,
+    pub token_id: String,
+    pub cause: String,
+    pub old_account_id: String,
+    pub new_account_id: String,
+}
@@ -0,0 +1,337 @@
+use std::str::FromStr;
+
+use crate::modules::transactions;
+use crate::{db_helpers, errors, types};
+
+pub(crate) async fn get_transaction(
+    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    db_helpers::BalancesPool(pool_balances): &db_helpers::BalancesPool,
+    transaction_hash: &str,
+) -> crate::Result<transactions::schemas::TransactionDetailsResponse> {
+    let query = r"
+        SELECT
+            transactions.transaction_hash,
+            transactions.signer_account_id,
+            transactions.receiver_account_id,
+            CASE WHEN execution_outcomes.status IN ('SUCCESS_VALUE', 'SUCCESS_RECEIPT_ID') THEN 'SUCCESS'
+                WHEN execution_outcomes.status = 'FAILURE' THEN 'FAILURE'
+                ELSE 'UNKNOWN'
+            END status,
+            transactions.receipt_conversion_gas_burnt + coalesce((
+                SELECT sum(outcomes.gas_burnt)
+                FROM receipts
+                    JOIN execution_outcomes outcomes ON receipts.receipt_id = outcomes.receipt_id
+                WHERE receipts.originated_from_transaction_hash = transactions.transaction_hash
+            ), 0) gas_burnt,
+            chunks.shard_id,
+            transactions.index_in_chunk,
+            transactions.block_timestamp block_timestamp_nanos,
+            blocks.block_height
+        FROM transactions
+            JOIN chunks ON transactions.included_in_chunk_hash = chunks.chunk_hash
+            JOIN blocks ON transactions.included_in_block_hash = blocks.block_hash
+            LEFT JOIN execution_outcomes ON transactions.converted_into_receipt_id = execution_outcomes.receipt_id
+        WHERE transactions.transaction_hash = $1
+    ";
+    let mut transaction = match db_helpers::select_retry_or_panic::<super::models::TransactionInfo>(
+        pool_explorer,
+        query,
+        &[transaction_hash.to_string()],
+    )
+    .await?
+    .pop()
+    {
+        Some(info) => transactions::schemas::Transaction::try_from(info)?,
+        None => {
+            return Err(errors::ErrorKind::InvalidInput(format!(
+                "transaction_hash {} is not found",
+                transaction_hash
+            ))
+            .into())
+        }
+    };
+    transaction.actions =
+        super::actions::get_actions_by_transaction(pool_explorer, &[transaction_hash.to_string()])
+            .await?
+            .remove(transaction_hash)
+            .unwrap_or_default();
+    transaction.deposit = super::actions::get_deposit(&transaction.actions)?.into();
+
+    let receipts = get_receipts(pool_explorer, transaction_hash).await?;
+    // Some balance changes (e.g. gas prepayment) are attributed to the transaction itself
+    let mut ids: Vec<String> = receipts
+        .iter()
+        .map(|receipt| receipt.receipt_id.clone())
+        .collect();
+    ids.push(transaction_hash.to_string());
+
+    Ok(transactions::schemas::TransactionDetailsResponse {
+        transaction,
+        receipts,
+        near_balance_changes: get_near_balance_changes(pool_balances, &ids).await?,
+        ft_balance_changes: get_ft_balance_changes(pool_balances, &ids).await?,
+        nft_changes: get_nft_changes(pool_explorer, &ids).await?,
+    })
+}
+
+async fn get_receipts(
+    pool_explorer: &sqlx::Pool<sqlx::Postgres>,
+    transaction_hash: &str,
+) -> crate::Result<Vec<transactions::schemas::Receipt>> {
+    let query = r"
+        SELECT
+            receipts.receipt_id,
+            execution_outcome_receipts.executed_receipt_id parent_receipt_id,
+            receipts.predecessor_account_id,
+            receipts.receiver_account_id,
+            receipts.receipt_kind::text kind,
+            CASE WHEN execution_outcomes.status IN ('SUCCESS_VALUE', 'SUCCESS_RECEIPT_ID') THEN 'SUCCESS'
+                WHEN execution_outcomes.status = 'FAILURE' THEN 'FAILURE'
+                ELSE 'UNKNOWN'
+            END status,
+            coalesce(execution_outcomes.gas_burnt, 0) gas_burnt,
+            coalesce(execution_outcomes.tokens_burnt, 0) tokens_burnt,
+            receipts.included_in_block_timestamp block_timestamp_nanos,
+            blocks.block_height
+        FROM receipts
+            JOIN blocks ON receipts.included_in_block_hash = blocks.block_hash
+            LEFT JOIN execution_outcomes ON receipts.receipt_id = execution_outcomes.receipt_id
+            LEFT JOIN execution_outcome_receipts ON receipts.receipt_id = execution_outcome_receipts.produced_receipt_id
+        WHERE receipts.originated_from_transaction_hash = $1
+        ORDER BY receipts.included_in_block_timestamp, receipts.index_in_chunk
+    ";
+    let receipts_info = db_helpers::select_retry_or_panic::<super::models::ReceiptInfo>(
+        pool_explorer,
+        query,
+        &[transaction_hash.to_string()],
+    )
+    .await?;
+
+    let receipt_ids: Vec<String> = receipts_info
+        .iter()
+        .map(|info| info.receipt_id.clone())
+        .collect();
+    let mut actions = super::actions::get_actions_by_receipt(pool_explorer, &receipt_ids).await?;
+
+    let mut result: Vec<transactions::schemas::Receipt> = vec![];
+    for info in receipts_info {
+        let mut receipt = transactions::schemas::Receipt::try_from(info)?;
+        receipt.actions = actions.remove(&receipt.receipt_id).unwrap_or_default();
+        result.push(receipt);
+    }
+    Ok(result)
+}
+
+async fn get_near_balance_changes(
+    pool_balances: &sqlx::Pool<sqlx::Postgres>,
+    ids: &[String],
+) -> crate::Result<Vec<transactions::schemas::NearBalanceChange>> {
+    let query = r"
+        SELECT
+            receipt_id,
+            affected_account_id,
+            involved_account_id,
+            delta_nonstaked_amount + delta_staked_amount delta_balance,
+            cause,
+            status
+        FROM near_balance_events
+        WHERE receipt_id = ANY($1::text[])
+        ORDER BY event_index
+    ";
+    let changes = db_helpers::select_retry_or_panic::<super::models::NearBalanceChangeInfo>(
+        pool_balances,
+        query,
+        &[db_helpers::to_sql_array(ids)],
+    )
+    .await?;
+
+    let mut result: Vec<transactions::schemas::NearBalanceChange> = vec![];
+    for change in changes {
+        result.push(change.try_into()?);
+    }
+    Ok(result)
+}
+
+async fn get_ft_balance_changes(
+    pool_balances: &sqlx::Pool<sqlx::Postgres>,
+    ids: &[String],
+) -> crate::Result<Vec<transactions::schemas::FtBalanceChange>> {
+    let query = r"
+        SELECT
+            receipt_id,
+            contract_account_id,
+            affected_account_id,
+            involved_account_id,
+            delta_amount delta_balance,
+            cause,
+            status
+        FROM fungible_token_events
+        WHERE receipt_id = ANY($1::text[])
+        ORDER BY event_index
+    ";
+    let changes = db_helpers::select_retry_or_panic::<super::models::FtBalanceChangeInfo>(
+        pool_balances,
+        query,
+        &[db_helpers::to_sql_array(ids)],
+    )
+    .await?;
+
+    let mut result: Vec<transactions::schemas::FtBalanceChange> = vec![];
+    for change in changes {
+        result.push(change.try_into()?);
+    }
+    Ok(result)
+}
+
+async fn get_nft_changes(
+    pool_explorer: &sqlx::Pool<sqlx::Postgres>,
+    ids: &[String],
+) -> crate::Result<Vec<transactions::schemas::NftChange>> {
+    let query = r"
+        SELECT
+            emitted_for_receipt_id receipt_id,
+            emitted_by_contract_account_id contract_account_id,
+            token_id,
+            event_kind::text cause,
+            token_old_owner_account_id old_account_id,
+            token_new_owner_account_id new_account_id
+        FROM assets__non_fungible_token_events
+        WHERE emitted_for_receipt_id = ANY($1::text[])
+        ORDER BY emitted_at_block_timestamp, emitted_in_shard_id, emitted_index_of_event_entry_in_shard
+    ";
+    let changes = db_helpers::select_retry_or_panic::<super::models::NftChangeInfo>(
+        pool_explorer,
+        query,
+        &[db_helpers::to_sql_array(ids)],
+    )
+    .await?;
+
+    let mut result: Vec<transactions::schemas::NftChange> = vec![];
+    for change in changes {
+        result.push(change.try_into()?);
+    }
+    Ok(result)
+}
+
+impl TryFrom<super::models::ReceiptInfo> for transactions::schemas::Receipt {
+    type Error = errors::Error;
+
+    fn try_from(info: super::models::ReceiptInfo) -> crate::Result<Self> {
+        Ok(Self {
+            receipt_id: info.receipt_id,
+            parent_receipt_id: info.parent_receipt_id,
+            predecessor_account_id: types::AccountId::from_str(&info.predecessor_account_id)?,
+            receiver_account_id: types::AccountId::from_str(&info.receiver_account_id)?,
+            kind: info.kind,
+            actions: vec![],
+            status: info.status,
+            gas_burnt: types::numeric::to_u64(&info.gas_burnt)?.into(),
+            tokens_burnt: types::numeric::to_u128(&info.tokens_burnt)?.into(),
+            block_timestamp_nanos: types::numeric::to_u64(&info.block_timestamp_nanos)?.into(),
+            block_height: types::numeric::to_u64(&info.block_height)?.into(),
+        })
+    }
+}
+
+impl TryFrom<super::models::NearBalanceChangeInfo> for transactions::schemas::NearBalanceChange {
+    type Error = errors::Error;
+
+    fn try_from(info: super::models::NearBalanceChangeInfo) -> crate::Result<Self> {
+        Ok(Self {
+            receipt_id: info.receipt_id,
+            affected_account_id: types::AccountId::from_str(&info.affected_account_id)?,
+            involved_account_id: match info.involved_account_id {
+                Some(id) => Some(types::AccountId::from_str(&id)?),
+                None => None,
+            },
+            delta_balance: info.delta_balance.to_string(),
+            cause: info.cause,
+            status: info.status,
+        })
+    }
+}
+
+impl TryFrom<super::models::FtBalanceChangeInfo> for transactions::schemas::FtBalanceChange {
+    type Error = errors::Error;
+
+    fn try_from(info: super::models::FtBalanceChangeInfo) -> crate::Result<Self> {
+        Ok(Self {
+            receipt_id: info.receipt_id,
+            contract_account_id: types::AccountId::from_str(&info.contract_account_id)?,
+            affected_account_id: types::AccountId::from_str(&info.affected_account_id)?,
+            involved_account_id: match info.involved_account_id {
+                Some(id) => Some(types::AccountId::from_str(&id)?),
+                None => None,
+            },
+            delta_balance: info.delta_balance.to_string(),
+            cause: info.cause,
+            status: info.status,
+        })
+    }
+}
+
+impl TryFrom<super::models::NftChangeInfo> for transactions::schemas::NftChange {
+    type Error = errors::Error;
+
+    fn try_from(info: super::models::NftChangeInfo) -> crate::Result<Self> {
+        Ok(Self {
+            receipt_id: info.receipt_id,
+            contract_account_id: types::AccountId::from_str(&info.contract_account_id)?,
+            token_id: info.token_id,
+            cause: info.cause,
+            old_account_id: types::account_id::extract_account_id(&info.old_account_id)?
+                .map(|account| account.into()),
+            new_account_id: types::account_id::extract_account_id(&info.new_account_id)?
+                .map(|account| account.into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+
+    #[tokio::test]
+    async fn test_transaction() {
+        let pool_explorer = init_explorer_db().await;
+        let pool_balances = init_balances_db().await;
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 1,
+            after_event_index: None,
+        };
+        let transaction_hash =
+            super::super::get_account_transactions(&pool_explorer, &account, &block, &pagination)
+                .await
+                .unwrap()
+                .first()
+                .unwrap()
+                .transaction_hash
+                .clone();
+
+        let transaction = get_transaction(&pool_explorer, &pool_balances, &transaction_hash)
+            .await
+            .unwrap();
+        assert_eq!(transaction.transaction.transaction_hash, transaction_hash);
+        assert!(!transaction.receipts.is_empty());
+        assert!(transaction
+            .receipts
+            .first()
+            .unwrap()
+            .parent_receipt_id
+            .is_none());
+        assert!(!transaction.near_balance_changes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_does_not_exist() {
+        let pool_explorer = init_explorer_db().await;
+        let pool_balances = init_balances_db().await;
+        let transaction_hash = "11111111111111111111111111111111";
+
+        let transaction = get_transaction(&pool_explorer, &pool_balances, transaction_hash).await;
+        assert!(transaction.is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use std::str::FromStr;
+
+use crate::modules::transactions;
+use crate::{cache, db_helpers, errors, types};
+
+/// The transactions at the old blocks never change, so they are cached (see `cache::cached`)
+pub(crate) async fn get_account_transactions(
+    pool_explorer: &db_helpers::ExplorerPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    pagination: &types::query_params::Pagination,
+) -> crate::Result<Vec<transactions::schemas::Transaction>> {
+    cache::cached(
+        "get_account_transactions",
+        format!("{}|{:?}", account_id, pagination),
+        block.height,
+        load_account_transactions(pool_explorer, account_id, block, pagination),
+    )
+    .await
+}
+
+async fn load_account_transactions(
+    db_helpers::ExplorerPool(pool_explorer): &db_helpers::ExplorerPool,
+    account_id: &near_primitives::types::AccountId,
+    block: &db_helpers::Block,
+    pagination: &types::query_params::Pagination,
+) -> crate::Result<Vec<transactions::schemas::Transaction>> {
+    // event_index for the transactions has the same layout as for NEAR balance events,
+    // shard_id is used as chunk_index, the transaction index in the chunk is used as index_in_chunk
+    let (after_timestamp, after_shard_id, after_index_in_chunk) =
+        if let Some(index) = pagination.after_event_index {
+            let parts = types::EventIndex::new(index).balance_parts();
+            (
+                parts.block_timestamp_nanos.0,
+                parts.chunk_index,
+                parts.index_in_chunk,
+            )
+        } else {
+            // +1 because we need to include given timestamp to result. Query has strict less operator
+            (block.timestamp + 1, 0, 0)
+        };
+
+    let query = r"
+        WITH account_transactions AS (
+            SELECT
+                transactions.transaction_hash,
+                transactions.signer_account_id,
+                transactions.receiver_account_id,
+                transactions.converted_into_receipt_id,
+                transactions.receipt_conversion_gas_burnt,
+                transactions.included_in_block_hash,
+                transactions.block_timestamp,
+                chunks.shard_id,
+                transactions.index_in_chunk
+            FROM transactions
+                JOIN chunks ON transactions.included_in_chunk_hash = chunks.chunk_hash
+            WHERE (transactions.signer_account_id = $1 OR transactions.receiver_account_id = $1)
+                AND (transactions.block_timestamp, chunks.shard_id, transactions.index_in_chunk)
+                    < ($2::numeric(20, 0), $3::numeric(20, 0), $4::integer)
+            ORDER BY transactions.block_timestamp DESC, chunks.shard_id DESC, transactions.index_in_chunk DESC
+            LIMIT $5::numeric(20, 0)
+        )
+        SELECT
+            account_transactions.transaction_hash,
+            account_transactions.signer_account_id,
+            account_transactions.receiver_account_id,
+            CASE WHEN execution_outcomes.status IN ('SUCCESS_VALUE', 'SUCCESS_RECEIPT_ID') THEN 'SUCCESS'
+                WHEN execution_outcomes.status = 'FAILURE' THEN 'FAILURE'
+                ELSE 'UNKNOWN'
+            END status,
+            account_transactions.receipt_conversion_gas_burnt + coalesce((
+                SELECT sum(outcomes.gas_burnt)
+                FROM receipts
+                    JOIN execution_outcomes outcomes ON receipts.receipt_id = outcomes.receipt_id
+                WHERE receipts.originated_from_transaction_hash = account_transactions.transaction_hash
+            ), 0) gas_burnt,
+            account_transactions.shard_id,
+            account_transactions.index_in_chunk,
+            account_transactions.block_timestamp block_timestamp_nanos,
+            blocks.block_height
+        FROM account_transactions
+            JOIN blocks ON account_transactions.included_in_block_hash = blocks.block_hash
+            LEFT JOIN execution_outcomes ON account_transactions.converted_into_receipt_id = execution_outcomes.receipt_id
+        ORDER BY account_transactions.block_timestamp DESC,
+            account_transactions.shard_id DESC,
+            account_transactions.index_in_chunk DESC
+    ";
+    let transactions_info = db_helpers::select_retry_or_panic::<super::models::TransactionInfo>(
+        pool_explorer,
+        query,
+        &[
+            account_id.to_string(),
+            after_timestamp.to_string(),
+            after_shard_id.to_string(),
+            after_index_in_chunk.to_string(),
+            pagination.limit.to_string(),
+        ],
+    )
+    .await?;
+
+    let transaction_hashes: Vec<String> = transactions_info
+        .iter()
+        .map(|info| info.transaction_hash.clone())
+        .collect();
+    let mut actions =
+        super::actions::get_actions_by_transaction(pool_explorer, &transaction_hashes).await?;
+
+    let mut result: Vec<transactions::schemas::Transaction> = vec![];
+    for info in transactions_info {
+        let mut transaction = transactions::schemas::Transaction::try_from(info)?;
+        transaction.actions = actions
+            .remove(&transaction.transaction_hash)
+            .unwrap_or_default();
+        transaction.deposit = super::actions::get_deposit(&transaction.actions)?.into();
+        result.push(transaction);
+    }
+    Ok(result)
+}
+
+impl TryFrom<super::models::TransactionInfo> for transactions::schemas::Transaction {
+    type Error = errors::Error;
+
+    fn try_from(info: super::models::TransactionInfo) -> crate::Result<Self> {
+        let block_timestamp_nanos = types::numeric::to_u64(&info.block_timestamp_nanos)?;
+        let event_index = types::EventIndex::from_balance_parts(
+            block_timestamp_nanos,
+            types::numeric::to_u64(&info.shard_id)? as u32,
+            info.index_in_chunk as u32,
+        )?;
+        Ok(Self {
+            event_index: u128::from(event_index).into(),
+            transaction_hash: info.transaction_hash,
+            signer_account_id: types::AccountId::from_str(&info.signer_account_id)?,
+            receiver_account_id: types::AccountId::from_str(&info.receiver_account_id)?,
+            actions: vec![],
+            deposit: types::U128(0),
+            gas_burnt: types::numeric::to_u64(&info.gas_burnt)?.into(),
+            status: info.status,
+            block_timestamp_nanos: block_timestamp_nanos.into(),
+            block_height: types::numeric::to_u64(&info.block_height)?.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tests::*;
+
+    #[tokio::test]
+    async fn test_account_transactions() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 5,
+            after_event_index: None,
+        };
+
+        let transactions = get_account_transactions(&pool_explorer, &account, &block, &pagination)
+            .await
+            .unwrap();
+        assert!(!transactions.is_empty());
+        for transaction in &transactions {
+            assert!(
+                transaction.signer_account_id.0 == account
+                    || transaction.receiver_account_id.0 == account
+            );
+            assert!(!transaction.actions.is_empty());
+            assert!(transaction.block_timestamp_nanos.0 <= block.timestamp);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_account_transactions_next_page() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let account = near_primitives::types::AccountId::from_str("cvirkun.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 5,
+            after_event_index: None,
+        };
+        let page1 = get_account_transactions(&pool_explorer, &account, &block, &pagination)
+            .await
+            .unwrap();
+
+        let pagination = types::query_params::Pagination {
+            limit: 5,
+            after_event_index: Some(page1.last().unwrap().event_index.0),
+        };
+        let block = db_helpers::get_block_from_pagination(&pool_explorer, &pagination)
+            .await
+            .unwrap();
+        let page2 = get_account_transactions(&pool_explorer, &account, &block, &pagination)
+            .await
+            .unwrap();
+
+        assert!(
+            page1.last().unwrap().event_index > page2.first().unwrap().event_index,
+            "Next page should not include transactions from previous page"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_account_transactions_account_never_existed() {
+        let pool_explorer = init_explorer_db().await;
+        let block = get_block();
+        let account =
+            near_primitives::types::AccountId::from_str("two-idiots-and-a-half.near").unwrap();
+        let pagination = types::query_params::Pagination {
+            limit: 10,
+            after_event_index: None,
+        };
+
+        let transactions = get_account_transactions(&pool_explorer, &account, &block, &pagination)
+            .await
+            .unwrap();
+        assert!(transactions.is_empty());
+    }
+}
@@ -0,0 +1,16 @@
+use paperclip::actix::web;
+
+mod data_provider;
+mod resources;
+mod schemas;
+
+pub(crate) fn register_services(app: &mut web::ServiceConfig) {
+    app.service(
+        web::resource("/accounts/{account_id}/transactions")
+            .route(web::get().to(resources::get_account_transactions)),
+    )
+    .service(
+        web::resource("/transactions/{transaction_hash}")
+            .route(web::get().to(resources::get_transaction)),
+    );
+}
@@ -0,0 +1,55 @@
+use paperclip::actix::{
+    api_v2_operation,
+    web::{self, Json},
+};
+
+use super::{data_provider, schemas};
+use crate::{db_helpers, modules, types};
+
+#[api_v2_operation(tags(Transactions))]
+/// Get user's transactions
+///
+/// This endpoint returns the transactions signed by the given `account_id`
+/// or sent to the given `account_id`, together with the actions, deposit, gas burnt and status.
+/// For the next page, use `event_index` of the last item in your previous response.
+pub async fn get_account_transactions(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::AccountTransactionsRequest>,
+    pagination_params: web::Query<types::query_params::PaginationParams>,
+) -> crate::Result<Json<schemas::TransactionsResponse>> {
+    let pagination = modules::checked_get_pagination_params(&pagination_params).await?;
+    let block = db_helpers::get_block_from_pagination(&pool_explorer, &pagination).await?;
+    // we don't need to check whether account exists. If not, we can just return the empty list
+
+    Ok(Json(schemas::TransactionsResponse {
+        transactions: data_provider::get_account_transactions(
+            &pool_explorer,
+            &request.account_id.0,
+            &block,
+            &pagination,
+        )
+        .await?,
+        block_timestamp_nanos: types::U64::from(block.timestamp),
+        block_height: types::U64::from(block.height),
+    }))
+}
+
+#[api_v2_operation(tags(Transactions))]
+/// Get transaction details
+///
+/// This endpoint returns the transaction for the given `transaction_hash`,
+/// together with all the receipts it produced and the actions they contain.
+/// `args_json` is added to FunctionCall actions if the arguments are valid JSON.
+/// The response also contains NEAR, FT and NFT balance changes caused by the transaction.
+pub async fn get_transaction(
+    pool_explorer: web::Data<db_helpers::ExplorerPool>,
+    pool_balances: web::Data<db_helpers::BalancesPool>,
+    _: crate::types::pagoda_api_key::PagodaApiKey,
+    request: actix_web_validator::Path<schemas::TransactionRequest>,
+) -> crate::Result<Json<schemas::TransactionDetailsResponse>> {
+    Ok(Json(
+        data_provider::get_transaction(&pool_explorer, &pool_balances, &request.transaction_hash)
+            .await?,
+    ))
+}
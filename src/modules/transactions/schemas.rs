@@ -0,0 +1,118 @@
+use paperclip::actix::Apiv2Schema;
+use validator::Validate;
+
+use crate::types;
+
+// *** Requests ***
+
+#[derive(
+    Validate, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema,
+)]
+pub struct AccountTransactionsRequest {
+    #[validate(custom = "crate::errors::validate_account_id")]
+    pub account_id: types::AccountId,
+}
+
+#[derive(
+    Validate, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema,
+)]
+pub struct TransactionRequest {
+    #[validate(custom = "crate::errors::validate_transaction_hash")]
+    pub transaction_hash: String,
+}
+
+// *** Responses ***
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct TransactionsResponse {
+    pub transactions: Vec<Transaction>,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct TransactionDetailsResponse {
+    pub transaction: Transaction,
+    pub receipts: Vec<Receipt>,
+    pub near_balance_changes: Vec<NearBalanceChange>,
+    pub ft_balance_changes: Vec<FtBalanceChange>,
+    pub nft_changes: Vec<NftChange>,
+}
+
+// ---
+
+/// This type describes the transaction signed by the user or sent to the user.
+/// `status` is one of ["SUCCESS", "FAILURE", "UNKNOWN"] and shows the result of the first receipt
+/// `gas_burnt` includes the gas burnt by all the receipts produced by the transaction
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct Transaction {
+    pub event_index: types::U128,
+    pub transaction_hash: String,
+    pub signer_account_id: types::AccountId,
+    pub receiver_account_id: types::AccountId,
+    pub actions: Vec<Action>,
+    /// Sum of deposits attached to all the actions
+    pub deposit: types::U128,
+    pub gas_burnt: types::U64,
+    pub status: String,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
+
+/// `kind` is one of ["CREATE_ACCOUNT", "DEPLOY_CONTRACT", "FUNCTION_CALL", "TRANSFER", "STAKE", "ADD_KEY", "DELETE_KEY", "DELETE_ACCOUNT"]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct Action {
+    pub kind: String,
+    pub args: types::JsonValue,
+}
+
+/// This type describes the receipt produced by the transaction.
+/// The receipts form the tree: the first receipt has no `parent_receipt_id`,
+/// it was created from the transaction itself. All the others were produced by `parent_receipt_id`.
+/// `kind` is one of ["ACTION", "DATA"]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct Receipt {
+    pub receipt_id: String,
+    pub parent_receipt_id: Option<String>,
+    pub predecessor_account_id: types::AccountId,
+    pub receiver_account_id: types::AccountId,
+    pub kind: String,
+    pub actions: Vec<Action>,
+    pub status: String,
+    pub gas_burnt: types::U64,
+    pub tokens_burnt: types::U128,
+    pub block_timestamp_nanos: types::U64,
+    pub block_height: types::U64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NearBalanceChange {
+    pub receipt_id: String,
+    pub affected_account_id: types::AccountId,
+    pub involved_account_id: Option<types::AccountId>,
+    pub delta_balance: String,
+    pub cause: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct FtBalanceChange {
+    pub receipt_id: String,
+    pub contract_account_id: types::AccountId,
+    pub affected_account_id: types::AccountId,
+    pub involved_account_id: Option<types::AccountId>,
+    pub delta_balance: String,
+    pub cause: String,
+    pub status: String,
+}
+
+/// `cause` is one of ["mint", "transfer", "burn"]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct NftChange {
+    pub receipt_id: String,
+    pub contract_account_id: types::AccountId,
+    pub token_id: String,
+    pub cause: String,
+    pub old_account_id: Option<types::AccountId>,
+    pub new_account_id: Option<types::AccountId>,
+}
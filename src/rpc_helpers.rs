@@ -1,12 +1,21 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use near_jsonrpc_primitives::types::query::{QueryResponseKind, RpcQueryError};
+use once_cell::sync::Lazy;
 
-use crate::errors;
+use crate::{cache, config, deadline, errors};
 
-const RPC_RETRY_COUNT: usize = 10;
+static RPC_RETRY_COUNT: Lazy<AtomicUsize> =
+    Lazy::new(|| AtomicUsize::new(config::RpcConfig::default().retry_count));
 
 const INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
 const MAX_DELAY_TIME: std::time::Duration = std::time::Duration::from_secs(120);
 
+/// Should be called once at startup, before the server starts
+pub(crate) fn init(rpc_config: &config::RpcConfig) {
+    RPC_RETRY_COUNT.store(rpc_config.retry_count, Ordering::Relaxed);
+}
+
 pub(crate) fn get_function_call_request(
     block_height: u64,
     account_id: near_primitives::types::AccountId,
@@ -25,23 +34,105 @@ pub(crate) fn get_function_call_request(
     }
 }
 
-fn clone_request(
-    request: &near_jsonrpc_client::methods::query::RpcQueryRequest,
-) -> near_jsonrpc_client::methods::query::RpcQueryRequest {
-    near_jsonrpc_client::methods::query::RpcQueryRequest {
-        block_reference: request.block_reference.clone(),
-        request: request.request.clone(),
-    }
+/// The result of the view call which could not be changed by retrying
+#[derive(Debug, Clone)]
+pub(crate) enum FunctionCallResult {
+    Success(near_primitives::views::CallResult),
+    /// The account has no contract, or the contract does not have such method
+    NotImplemented,
+    /// The method exists, but it fails with the given args (panic, gas limit, etc.)
+    Failed(String),
+}
+
+/// Performs the view call with retries.
+/// Use it if you need to handle the missing method or the contract failure, otherwise use `wrapped_call`
+pub(crate) async fn call_function(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    request: near_jsonrpc_client::methods::query::RpcQueryRequest,
+    block_height: u64,
+    contract_id: &near_primitives::types::AccountId,
+) -> crate::Result<FunctionCallResult> {
+    let args = format!("{:?}", request.request);
+    cache::cached("call_function", args, block_height, async move {
+        match wrapped_query(rpc_client, request, block_height, contract_id).await? {
+            Ok(QueryResponseKind::CallResult(result)) => Ok(FunctionCallResult::Success(result)),
+            // I hope this is unreachable code, so if we meet such case, retry will not help
+            Ok(_) => Err(errors::ErrorKind::RPCError(
+                "Unexpected type of the response after CallFunction request".to_string(),
+            )
+            .into()),
+            Err(vm_error)
+                if vm_error.contains("CodeDoesNotExist") || vm_error.contains("MethodNotFound") =>
+            {
+                Ok(FunctionCallResult::NotImplemented)
+            }
+            Err(vm_error) => Ok(FunctionCallResult::Failed(vm_error)),
+        }
+    })
+    .await
 }
 
 pub(crate) async fn wrapped_call(
-    rpc_client: &near_jsonrpc_client::JsonRpcClient,
+    rpc_client: &crate::rpc_pool::RpcPool,
     request: near_jsonrpc_client::methods::query::RpcQueryRequest,
     block_height: u64,
     contract_id: &near_primitives::types::AccountId,
 ) -> crate::Result<near_primitives::views::CallResult> {
+    match call_function(rpc_client, request, block_height, contract_id).await? {
+        FunctionCallResult::Success(result) => Ok(result),
+        FunctionCallResult::NotImplemented => Err(errors::ErrorKind::InvalidInput(format!(
+            "The account `{}` does not implement any suitable contract at block {}",
+            contract_id, block_height
+        ))
+        .into()),
+        FunctionCallResult::Failed(vm_error) => {
+            Err(errors::ErrorKind::ContractError(vm_error).into())
+        }
+    }
+}
+
+pub(crate) async fn view_access_key_list(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    block_height: u64,
+    account_id: &near_primitives::types::AccountId,
+) -> crate::Result<Vec<near_primitives::views::AccessKeyInfoView>> {
+    let request = near_jsonrpc_client::methods::query::RpcQueryRequest {
+        block_reference: near_primitives::types::BlockReference::BlockId(
+            near_primitives::types::BlockId::Height(block_height),
+        ),
+        request: near_primitives::views::QueryRequest::ViewAccessKeyList {
+            account_id: account_id.clone(),
+        },
+    };
+    cache::cached(
+        "view_access_key_list",
+        account_id.to_string(),
+        block_height,
+        async move {
+            match wrapped_query(rpc_client, request, block_height, account_id).await? {
+                Ok(QueryResponseKind::AccessKeyList(list)) => Ok(list.keys),
+                _ => Err(errors::ErrorKind::RPCError(
+                    "Unexpected type of the response after ViewAccessKeyList request".to_string(),
+                )
+                .into()),
+            }
+        },
+    )
+    .await
+}
+
+/// Performs any `query` request with retries.
+/// Errors that could not be fixed by retrying are returned immediately:
+/// the unknown account is an error, the contract failure is given as `Ok(Err(vm_error))`
+async fn wrapped_query(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    request: near_jsonrpc_client::methods::query::RpcQueryRequest,
+    block_height: u64,
+    contract_id: &near_primitives::types::AccountId,
+) -> crate::Result<Result<QueryResponseKind, String>> {
     let mut interval = INTERVAL;
     let mut retry_attempt = 0usize;
+    let retry_count = RPC_RETRY_COUNT.load(Ordering::Relaxed);
 
     tracing::info!(
         target: crate::LOGGER_MSG,
@@ -54,17 +145,8 @@ pub(crate) async fn wrapped_call(
     loop {
         retry_attempt += 1;
 
-        match rpc_client.call(clone_request(&request)).await {
-            Ok(response) => {
-                return match response.kind {
-                    QueryResponseKind::CallResult(result) => Ok(result),
-                    // I hope this is unreachable code, so if we meet such case, retry will not help
-                    _ => Err(errors::ErrorKind::RPCError(
-                        "Unexpected type of the response after CallFunction request".to_string(),
-                    )
-                    .into()),
-                };
-            }
+        match rpc_client.call(&request, Some(block_height)).await {
+            Ok(response) => return Ok(Ok(response.kind)),
             Err(near_jsonrpc_client::errors::JsonRpcError::ServerError(
                 near_jsonrpc_client::errors::JsonRpcServerError::HandlerError(
                     near_jsonrpc_client::methods::query::RpcQueryError::UnknownAccount { .. },
@@ -77,18 +159,11 @@ pub(crate) async fn wrapped_call(
                 .into())
             }
             Err(x) => {
+                // The contract gives the same result at the same block, no need to retry this
                 if let Some(RpcQueryError::ContractExecutionError { vm_error, .. }) =
                     x.handler_error()
                 {
-                    if vm_error.contains("CodeDoesNotExist") || vm_error.contains("MethodNotFound")
-                    {
-                        // no need to retry this
-                        return Err(errors::ErrorKind::InvalidInput(format!(
-                            "The account `{}` does not implement any suitable contract at block {}",
-                            contract_id, block_height
-                        ))
-                        .into());
-                    }
+                    return Ok(Err(vm_error.clone()));
                 }
 
                 tracing::warn!(
@@ -101,14 +176,15 @@ pub(crate) async fn wrapped_call(
                     interval.as_millis(),
                 );
 
-                if retry_attempt == RPC_RETRY_COUNT {
+                if retry_attempt == retry_count {
                     tracing::warn!(
                         target: crate::LOGGER_MSG,
                         "Failed to perform query to RPC after {} attempts. Stop trying.",
-                        RPC_RETRY_COUNT
+                        retry_count
                     );
                     return Err(x.into());
                 }
+                deadline::check_retry(interval)?;
                 tokio::time::sleep(interval).await;
                 if interval < MAX_DELAY_TIME {
                     interval *= 2;
@@ -117,3 +193,75 @@ pub(crate) async fn wrapped_call(
         }
     }
 }
+
+/// Returns the header of the latest block with the given finality, with retries
+pub(crate) async fn get_block_header(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    finality: near_primitives::types::Finality,
+) -> crate::Result<near_primitives::views::BlockHeaderView> {
+    let request = near_jsonrpc_client::methods::block::RpcBlockRequest {
+        block_reference: near_primitives::types::BlockReference::Finality(finality),
+    };
+    let mut interval = INTERVAL;
+    let mut retry_attempt = 0usize;
+    let retry_count = RPC_RETRY_COUNT.load(Ordering::Relaxed);
+
+    loop {
+        retry_attempt += 1;
+
+        match rpc_client.call(&request, None).await {
+            Ok(block) => return Ok(block.header),
+            Err(x) => {
+                tracing::warn!(
+                    target: crate::LOGGER_MSG,
+                    "Error occurred during {:#?}:\nFailed RPC request: {:?}\n Retrying in {} milliseconds...",
+                    x,
+                    request,
+                    interval.as_millis(),
+                );
+
+                if retry_attempt == retry_count {
+                    tracing::warn!(
+                        target: crate::LOGGER_MSG,
+                        "Failed to get the block from RPC after {} attempts. Stop trying.",
+                        retry_count
+                    );
+                    return Err(errors::ErrorKind::from(x).into());
+                }
+                deadline::check_retry(interval)?;
+                tokio::time::sleep(interval).await;
+                if interval < MAX_DELAY_TIME {
+                    interval *= 2;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the height of the latest block which can't be reverted
+pub(crate) async fn get_final_block_height(
+    rpc_client: &crate::rpc_pool::RpcPool,
+) -> crate::Result<u64> {
+    Ok(
+        get_block_header(rpc_client, near_primitives::types::Finality::Final)
+            .await?
+            .height,
+    )
+}
+
+/// Checks whether the contract has the given view method at the given block.
+/// The method is considered to be implemented even if it fails with the given args:
+/// we only need to know that it exists
+pub(crate) async fn is_method_implemented(
+    rpc_client: &crate::rpc_pool::RpcPool,
+    block_height: u64,
+    contract_id: &near_primitives::types::AccountId,
+    method_name: &str,
+    args: serde_json::Value,
+) -> crate::Result<bool> {
+    let request = get_function_call_request(block_height, contract_id.clone(), method_name, args);
+    Ok(!matches!(
+        call_function(rpc_client, request, block_height, contract_id).await?,
+        FunctionCallResult::NotImplemented
+    ))
+}
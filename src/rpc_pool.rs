@@ -0,0 +1,444 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use near_jsonrpc_client::errors::{JsonRpcError, JsonRpcServerError};
+use near_jsonrpc_client::methods::RpcMethod;
+use once_cell::sync::Lazy;
+
+use crate::{cache, config};
+
+const RESULT_SUCCESS: &str = "success";
+const RESULT_FAILURE: &str = "failure";
+const MISSING_BLOCK_ERRORS: &[&str] = &["UNKNOWN_BLOCK", "GARBAGE_COLLECTED_BLOCK"];
+
+static RPC_REQUESTS: Lazy<prometheus::IntCounterVec> = Lazy::new(|| {
+    prometheus::IntCounterVec::new(
+        prometheus::Opts::new(
+            "api_rpc_requests_total",
+            "Number of requests to RPC endpoints",
+        ),
+        &["endpoint", "result"],
+    )
+    .expect("Failed to create api_rpc_requests_total metric")
+});
+static RPC_ENDPOINT_HEALTHY: Lazy<prometheus::IntGaugeVec> = Lazy::new(|| {
+    prometheus::IntGaugeVec::new(
+        prometheus::Opts::new(
+            "api_rpc_endpoint_healthy",
+            "1 if the RPC endpoint is used, 0 if it is skipped after the failures",
+        ),
+        &["endpoint"],
+    )
+    .expect("Failed to create api_rpc_endpoint_healthy metric")
+});
+
+/// Should be called once at startup, before the server starts
+pub(crate) fn init(registry: &prometheus::Registry) -> prometheus::Result<()> {
+    registry.register(Box::new(RPC_REQUESTS.clone()))?;
+    registry.register(Box::new(RPC_ENDPOINT_HEALTHY.clone()))
+}
+
+struct RpcEndpoint {
+    // We don't put URL to the metrics and logs, it may contain the API key
+    name: String,
+    client: near_jsonrpc_client::JsonRpcClient,
+    is_archival: bool,
+    consecutive_failures: AtomicU32,
+    unhealthy_until_ms: AtomicU64,
+}
+
+/// The set of RPC endpoints we use with failover.
+/// The requests to the old blocks go to the archival nodes, the others prefer the regular nodes.
+/// The endpoint is skipped for a while after several failures in a row
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    archival_threshold_blocks: u64,
+    unhealthy_after_failures: u32,
+    unhealthy_cooldown_ms: u64,
+}
+
+impl RpcPool {
+    pub(crate) fn new(rpc_config: &config::RpcConfig) -> Self {
+        // The single `url` is the archival one, we used to make all the requests there
+        let endpoints = if rpc_config.endpoints.is_empty() {
+            vec![config::RpcEndpointConfig {
+                url: rpc_config.url.clone(),
+                archival: true,
+            }]
+        } else {
+            rpc_config.endpoints.clone()
+        };
+        // The timeout is applied to each endpoint, so that we have time to try the next one
+        let http_client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(rpc_config.timeout_ms))
+            .build()
+            .expect("Failed to create RPC HTTP client");
+        Self {
+            endpoints: endpoints
+                .iter()
+                .enumerate()
+                .map(|(index, endpoint)| {
+                    let name = format!(
+                        "{}-{}",
+                        if endpoint.archival {
+                            "archival"
+                        } else {
+                            "regular"
+                        },
+                        index
+                    );
+                    RPC_ENDPOINT_HEALTHY.with_label_values(&[&name]).set(1);
+                    RpcEndpoint {
+                        name,
+                        client: near_jsonrpc_client::JsonRpcClient::with(http_client.clone())
+                            .connect(&endpoint.url),
+                        is_archival: endpoint.archival,
+                        consecutive_failures: AtomicU32::new(0),
+                        unhealthy_until_ms: AtomicU64::new(0),
+                    }
+                })
+                .collect(),
+            archival_threshold_blocks: rpc_config.archival_threshold_blocks,
+            unhealthy_after_failures: rpc_config.unhealthy_after_failures,
+            unhealthy_cooldown_ms: rpc_config.unhealthy_cooldown_ms,
+        }
+    }
+
+    /// Sends the request to the best endpoint, goes to the next one if the endpoint does not answer
+    /// or if the regular node does not have the block.
+    /// `block_height` is used to choose between archival and regular nodes, None means the latest block
+    pub(crate) async fn call<M>(
+        &self,
+        method: &M,
+        block_height: Option<u64>,
+    ) -> near_jsonrpc_client::MethodCallResult<M::Response, M::Error>
+    where
+        M: RpcMethod,
+        M::Error: std::fmt::Debug + serde::Serialize,
+    {
+        let mut last_error = None;
+        for endpoint in self.get_endpoints(block_height, cache::get_latest_block_height()) {
+            match endpoint.client.call(method).await {
+                Err(err) if is_endpoint_failure(&err) => {
+                    tracing::warn!(
+                        target: crate::LOGGER_MSG,
+                        "RPC endpoint {} failed: {:?}",
+                        endpoint.name,
+                        err
+                    );
+                    self.on_failure(endpoint);
+                    last_error = Some(err);
+                }
+                // The node works fine, it just does not keep the old blocks
+                Err(err) if !endpoint.is_archival && is_block_missing(&err) => {
+                    tracing::debug!(
+                        target: crate::LOGGER_MSG,
+                        "RPC endpoint {} does not have the block: {:?}",
+                        endpoint.name,
+                        err
+                    );
+                    self.on_success(endpoint);
+                    last_error = Some(err);
+                }
+                result => {
+                    self.on_success(endpoint);
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("RPC pool should have at least one endpoint"))
+    }
+
+    // Healthy endpoints go first, but we still try the others if all of them fail.
+    // `latest_block_height` is 0 until we read the latest block from the DB
+    fn get_endpoints(
+        &self,
+        block_height: Option<u64>,
+        latest_block_height: u64,
+    ) -> Vec<&RpcEndpoint> {
+        let is_historical = block_height.map_or(false, |block_height| {
+            block_height.saturating_add(self.archival_threshold_blocks) < latest_block_height
+        });
+        // We can't say whether the block is old, the archival node has it for sure
+        let prefer_archival = block_height.is_some() && latest_block_height == 0;
+        let has_archival = self.endpoints.iter().any(|endpoint| endpoint.is_archival);
+
+        let mut endpoints: Vec<&RpcEndpoint> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_archival || !is_historical || !has_archival)
+            .collect();
+        let now_ms = get_now_ms();
+        // sort_by_key is stable, so we keep the order from the config inside the groups
+        endpoints.sort_by_key(|endpoint| {
+            (
+                endpoint.unhealthy_until_ms.load(Ordering::Relaxed) > now_ms,
+                endpoint.is_archival != prefer_archival,
+            )
+        });
+        endpoints
+    }
+
+    fn on_success(&self, endpoint: &RpcEndpoint) {
+        RPC_REQUESTS
+            .with_label_values(&[&endpoint.name, RESULT_SUCCESS])
+            .inc();
+        endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+        endpoint.unhealthy_until_ms.store(0, Ordering::Relaxed);
+        RPC_ENDPOINT_HEALTHY
+            .with_label_values(&[&endpoint.name])
+            .set(1);
+    }
+
+    fn on_failure(&self, endpoint: &RpcEndpoint) {
+        RPC_REQUESTS
+            .with_label_values(&[&endpoint.name, RESULT_FAILURE])
+            .inc();
+        let failures = endpoint
+            .consecutive_failures
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        if failures >= self.unhealthy_after_failures {
+            endpoint
+                .unhealthy_until_ms
+                .store(get_now_ms() + self.unhealthy_cooldown_ms, Ordering::Relaxed);
+            RPC_ENDPOINT_HEALTHY
+                .with_label_values(&[&endpoint.name])
+                .set(0);
+        }
+    }
+}
+
+// The errors from the handler are the valid answers, the node works fine
+fn is_endpoint_failure<E>(err: &JsonRpcError<E>) -> bool {
+    !matches!(
+        err,
+        JsonRpcError::ServerError(
+            JsonRpcServerError::HandlerError(_) | JsonRpcServerError::RequestValidationError(_)
+        )
+    )
+}
+
+// The regular nodes answer with these errors for the blocks they have already dropped.
+// The handler errors of all the methods are serialized as `{"name": ..., "info": ...}`
+fn is_block_missing<E: serde::Serialize>(err: &JsonRpcError<E>) -> bool {
+    match err {
+        JsonRpcError::ServerError(JsonRpcServerError::HandlerError(handler_error)) => {
+            serde_json::to_value(handler_error)
+                .ok()
+                .and_then(|value| {
+                    value
+                        .get("name")
+                        .and_then(|name| name.as_str())
+                        .map(|name| MISSING_BLOCK_ERRORS.contains(&name))
+                })
+                .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+fn get_now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_pool() -> RpcPool {
+        RpcPool::new(&config::RpcConfig {
+            endpoints: vec![
+                config::RpcEndpointConfig {
+                    url: "http://archival.example".to_string(),
+                    archival: true,
+                },
+                config::RpcEndpointConfig {
+                    url: "http://regular.example".to_string(),
+                    archival: false,
+                },
+            ],
+            archival_threshold_blocks: 1000,
+            unhealthy_after_failures: 2,
+            ..Default::default()
+        })
+    }
+
+    fn get_names(endpoints: Vec<&RpcEndpoint>) -> Vec<&str> {
+        endpoints
+            .into_iter()
+            .map(|endpoint| endpoint.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_endpoints_routing() {
+        let pool = get_pool();
+        assert_eq!(
+            get_names(pool.get_endpoints(None, 10_000_000)),
+            vec!["regular-1", "archival-0"]
+        );
+        assert_eq!(
+            get_names(pool.get_endpoints(Some(10_000_000), 10_000_000)),
+            vec!["regular-1", "archival-0"]
+        );
+        assert_eq!(
+            get_names(pool.get_endpoints(Some(1), 10_000_000)),
+            vec!["archival-0"]
+        );
+        // We have not seen the latest block yet
+        assert_eq!(
+            get_names(pool.get_endpoints(Some(1), 0)),
+            vec!["archival-0", "regular-1"]
+        );
+        assert_eq!(
+            get_names(pool.get_endpoints(None, 0)),
+            vec!["regular-1", "archival-0"]
+        );
+    }
+
+    // Answers any request with the given JSON-RPC response
+    fn start_rpc_mock(response: serde_json::Value) -> String {
+        let server = actix_web::HttpServer::new(move || {
+            let response = response.clone();
+            actix_web::App::new().default_service(actix_web::web::to(move || {
+                let response = response.clone();
+                async move { actix_web::HttpResponse::Ok().json(response) }
+            }))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let addr = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+        format!("http://{}", addr)
+    }
+
+    fn get_mock_pool(regular_url: String, archival_url: String) -> RpcPool {
+        RpcPool::new(&config::RpcConfig {
+            endpoints: vec![
+                config::RpcEndpointConfig {
+                    url: regular_url,
+                    archival: false,
+                },
+                config::RpcEndpointConfig {
+                    url: archival_url,
+                    archival: true,
+                },
+            ],
+            timeout_ms: 1000,
+            ..Default::default()
+        })
+    }
+
+    fn get_gas_price_request() -> near_jsonrpc_client::methods::gas_price::RpcGasPriceRequest {
+        near_jsonrpc_client::methods::gas_price::RpcGasPriceRequest {
+            block_id: Some(near_primitives::types::BlockId::Height(1)),
+        }
+    }
+
+    fn get_gas_price_response() -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "dontcare",
+            "result": { "gas_price": "100000000" }
+        })
+    }
+
+    #[actix_web::test]
+    async fn test_failover_unavailable_endpoint() {
+        // Nobody listens on port 1
+        let pool = get_mock_pool(
+            "http://127.0.0.1:1".to_string(),
+            start_rpc_mock(get_gas_price_response()),
+        );
+
+        let response = pool.call(&get_gas_price_request(), None).await.unwrap();
+        assert_eq!(response.gas_price, 100000000);
+        assert_eq!(
+            pool.endpoints[0]
+                .consecutive_failures
+                .load(Ordering::Relaxed),
+            1
+        );
+        assert_eq!(
+            pool.endpoints[1]
+                .consecutive_failures
+                .load(Ordering::Relaxed),
+            0
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_failover_missing_block() {
+        let regular_url = start_rpc_mock(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "dontcare",
+            "error": {
+                "name": "HANDLER_ERROR",
+                "cause": {
+                    "name": "UNKNOWN_BLOCK",
+                    "info": { "error_message": "DB Not Found Error: BLOCK HEIGHT: 1" }
+                },
+                "code": -32000,
+                "message": "Server error",
+                "data": "DB Not Found Error: BLOCK HEIGHT: 1"
+            }
+        }));
+        let pool = get_mock_pool(
+            regular_url.clone(),
+            start_rpc_mock(get_gas_price_response()),
+        );
+
+        // The block is old, but we don't know it yet, so we go to the archival node first
+        let response = pool.call(&get_gas_price_request(), Some(1)).await.unwrap();
+        assert_eq!(response.gas_price, 100000000);
+
+        // The regular node goes first for the latest blocks,
+        // it does not have the block, but it's not a failure of the node
+        let response = pool.call(&get_gas_price_request(), None).await.unwrap();
+        assert_eq!(response.gas_price, 100000000);
+        assert_eq!(
+            pool.endpoints[0]
+                .consecutive_failures
+                .load(Ordering::Relaxed),
+            0
+        );
+
+        // Without the archival node, the error is given as is
+        let pool = RpcPool::new(&config::RpcConfig {
+            endpoints: vec![config::RpcEndpointConfig {
+                url: regular_url,
+                archival: false,
+            }],
+            ..Default::default()
+        });
+        assert!(is_block_missing(
+            &pool.call(&get_gas_price_request(), None).await.unwrap_err()
+        ));
+    }
+
+    #[test]
+    fn test_endpoints_health() {
+        let pool = get_pool();
+        let regular = &pool.endpoints[1];
+
+        pool.on_failure(regular);
+        assert_eq!(
+            get_names(pool.get_endpoints(None, 10_000_000)),
+            vec!["regular-1", "archival-0"]
+        );
+        pool.on_failure(regular);
+        assert_eq!(
+            get_names(pool.get_endpoints(None, 10_000_000)),
+            vec!["archival-0", "regular-1"]
+        );
+        pool.on_success(regular);
+        assert_eq!(
+            get_names(pool.get_endpoints(None, 10_000_000)),
+            vec!["regular-1", "archival-0"]
+        );
+    }
+}
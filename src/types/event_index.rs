@@ -0,0 +1,353 @@
+use paperclip::actix::Apiv2Schema;
+
+use crate::{errors, types};
+
+// See DB_DESIGN.md for the layout of event_index.
+// Indexers put the block timestamp in milliseconds multiplied by 10^22 to the highest digits.
+// It's the same as timestamp_nanos * 10^16 with the timestamp truncated to milliseconds,
+// so the lower 16 digits are used for the position of the event inside the block.
+// The event indexes we build ourselves (actions, NFT events) keep the full nanoseconds
+const TIMESTAMP_MULTIPLIER: u128 = 10_u128.pow(16);
+
+// timestamp_millis * 10^22 + chunk_index * 10^7 + assets_type * 10^4 + index_of_event
+const ASSET_CHUNK_INDEX_MULTIPLIER: u128 = 10_u128.pow(7);
+const ASSET_TYPE_MULTIPLIER: u128 = 10_u128.pow(4);
+const MAX_ASSETS_TYPE: u128 = 1_000;
+
+// timestamp_millis * 10^22 + chunk_index * 10^6 + index_in_chunk
+const BALANCE_CHUNK_INDEX_MULTIPLIER: u128 = 10_u128.pow(6);
+
+// The actions do not have event_index in the DB, we build it from the action position:
+// timestamp_nanos * 10^16 + shard_id * 10^13 + index_in_chunk * 10^3 + index_in_action_receipt.
+// index_in_chunk is integer in the DB, so it always fits into 10 digits;
+// the receipt could have at most 100 actions
+const ACTION_SHARD_ID_MULTIPLIER: u128 = 10_u128.pow(13);
+const ACTION_INDEX_IN_CHUNK_MULTIPLIER: u128 = 10_u128.pow(3);
+
+// NFT events do not have event_index in the DB, we build it from the event position in the shard:
+// timestamp_nanos * 10^16 + shard_id * 10^10 + index_in_shard.
+// index_in_shard is integer in the DB, so it always fits into 10 digits
+const SHARD_EVENT_SHARD_ID_MULTIPLIER: u128 = 10_u128.pow(10);
+
+// Any valid event_index is at least 10^34, it corresponds to timestamps earlier than any NEAR block.
+// Lower values usually mean the user passed the timestamp or the block height instead of event_index
+const MIN_TIMESTAMP_NANOS: u64 = 10_u64.pow(18);
+// event_index is stored as numeric(38, 0)
+const MAX_EVENT_INDEX: u128 = 10_u128.pow(38) - 1;
+
+/// Typed representation of `event_index` column.
+/// Both assets (FT, NFT) and balances (NEAR) events share the timestamp part,
+/// but the lower digits are packed in a different way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct EventIndex(u128);
+
+/// Position of the action, decoded from `event_index` built by `EventIndex::from_action_parts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ActionIndexParts {
+    pub timestamp_nanos: u64,
+    pub shard_id: u32,
+    pub index_in_chunk: u32,
+    pub index_in_action_receipt: u32,
+}
+
+/// Decoded components of `event_index`.
+/// `assets_type` is provided only for FT, NFT events.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct EventIndexParts {
+    /// Millisecond precision for FT and NEAR events, the indexers do not store more
+    pub block_timestamp_nanos: types::U64,
+    pub chunk_index: u32,
+    pub assets_type: Option<u16>,
+    pub index_in_chunk: u32,
+}
+
+impl EventIndex {
+    /// Wraps the value taken from the DB. Use `checked_from` for the values received from the user
+    pub(crate) fn new(event_index: u128) -> Self {
+        Self(event_index)
+    }
+
+    /// Validates event_index received from the user
+    pub(crate) fn checked_from(event_index: u128) -> crate::Result<Self> {
+        let timestamp_nanos = event_index / TIMESTAMP_MULTIPLIER;
+        if timestamp_nanos < MIN_TIMESTAMP_NANOS as u128 {
+            return Err(errors::ErrorKind::InvalidInput(format!(
+                "event_index {} is too low. Please copy event_index value from the last item in your previous response",
+                event_index
+            ))
+            .into());
+        }
+        if event_index > MAX_EVENT_INDEX || timestamp_nanos > u64::MAX as u128 {
+            return Err(errors::ErrorKind::InvalidInput(format!(
+                "event_index {} is too high. Please copy event_index value from the last item in your previous response",
+                event_index
+            ))
+            .into());
+        }
+        Ok(Self(event_index))
+    }
+
+    /// The lowest event_index for the given timestamp.
+    /// Any event happened at this timestamp or later has event_index greater or equal to this value
+    pub(crate) fn from_timestamp(timestamp_nanos: u64) -> Self {
+        Self(timestamp_nanos as u128 * TIMESTAMP_MULTIPLIER)
+    }
+
+    pub(crate) fn from_asset_parts(
+        timestamp_nanos: u64,
+        chunk_index: u32,
+        assets_type: u16,
+        index_of_event: u16,
+    ) -> crate::Result<Self> {
+        let chunk_index = chunk_index as u128;
+        let assets_type = assets_type as u128;
+        let index_of_event = index_of_event as u128;
+        if chunk_index >= TIMESTAMP_MULTIPLIER / ASSET_CHUNK_INDEX_MULTIPLIER
+            || assets_type >= MAX_ASSETS_TYPE
+            || index_of_event >= ASSET_TYPE_MULTIPLIER
+        {
+            return Err(errors::ErrorKind::InternalError(format!(
+                "Could not encode event_index from chunk_index {}, assets_type {}, index_of_event {}",
+                chunk_index, assets_type, index_of_event
+            ))
+            .into());
+        }
+        Ok(Self(
+            Self::from_timestamp(timestamp_nanos).0
+                + chunk_index * ASSET_CHUNK_INDEX_MULTIPLIER
+                + assets_type * ASSET_TYPE_MULTIPLIER
+                + index_of_event,
+        ))
+    }
+
+    pub(crate) fn from_balance_parts(
+        timestamp_nanos: u64,
+        chunk_index: u32,
+        index_in_chunk: u32,
+    ) -> crate::Result<Self> {
+        let chunk_index = chunk_index as u128;
+        let index_in_chunk = index_in_chunk as u128;
+        if chunk_index >= TIMESTAMP_MULTIPLIER / BALANCE_CHUNK_INDEX_MULTIPLIER
+            || index_in_chunk >= BALANCE_CHUNK_INDEX_MULTIPLIER
+        {
+            return Err(errors::ErrorKind::InternalError(format!(
+                "Could not encode event_index from chunk_index {}, index_in_chunk {}",
+                chunk_index, index_in_chunk
+            ))
+            .into());
+        }
+        Ok(Self(
+            Self::from_timestamp(timestamp_nanos).0
+                + chunk_index * BALANCE_CHUNK_INDEX_MULTIPLIER
+                + index_in_chunk,
+        ))
+    }
+
+    pub(crate) fn from_action_parts(parts: ActionIndexParts) -> crate::Result<Self> {
+        let shard_id = parts.shard_id as u128;
+        let index_in_chunk = parts.index_in_chunk as u128;
+        let index_in_action_receipt = parts.index_in_action_receipt as u128;
+        if shard_id >= TIMESTAMP_MULTIPLIER / ACTION_SHARD_ID_MULTIPLIER
+            || index_in_chunk >= ACTION_SHARD_ID_MULTIPLIER / ACTION_INDEX_IN_CHUNK_MULTIPLIER
+            || index_in_action_receipt >= ACTION_INDEX_IN_CHUNK_MULTIPLIER
+        {
+            return Err(errors::ErrorKind::InternalError(format!(
+                "Could not encode event_index from shard_id {}, index_in_chunk {}, index_in_action_receipt {}",
+                shard_id, index_in_chunk, index_in_action_receipt
+            ))
+            .into());
+        }
+        Ok(Self(
+            Self::from_timestamp(parts.timestamp_nanos).0
+                + shard_id * ACTION_SHARD_ID_MULTIPLIER
+                + index_in_chunk * ACTION_INDEX_IN_CHUNK_MULTIPLIER
+                + index_in_action_receipt,
+        ))
+    }
+
+    pub(crate) fn from_shard_event_parts(
+        timestamp_nanos: u64,
+        shard_id: u32,
+        index_in_shard: u32,
+    ) -> crate::Result<Self> {
+        let shard_id = shard_id as u128;
+        let index_in_shard = index_in_shard as u128;
+        if shard_id >= TIMESTAMP_MULTIPLIER / SHARD_EVENT_SHARD_ID_MULTIPLIER
+            || index_in_shard >= SHARD_EVENT_SHARD_ID_MULTIPLIER
+        {
+            return Err(errors::ErrorKind::InternalError(format!(
+                "Could not encode event_index from shard_id {}, index_in_shard {}",
+                shard_id, index_in_shard
+            ))
+            .into());
+        }
+        Ok(Self(
+            Self::from_timestamp(timestamp_nanos).0
+                + shard_id * SHARD_EVENT_SHARD_ID_MULTIPLIER
+                + index_in_shard,
+        ))
+    }
+
+    /// The timestamp is truncated to milliseconds for the event indexes stored by the indexers
+    pub(crate) fn timestamp_nanos(&self) -> u64 {
+        (self.0 / TIMESTAMP_MULTIPLIER) as u64
+    }
+
+    /// Decodes event_index from assets__* tables, fungible_token_events.
+    /// `block_timestamp_nanos` has millisecond precision, see `TIMESTAMP_MULTIPLIER`
+    pub(crate) fn asset_parts(&self) -> EventIndexParts {
+        let position = self.0 % TIMESTAMP_MULTIPLIER;
+        EventIndexParts {
+            block_timestamp_nanos: self.timestamp_nanos().into(),
+            chunk_index: (position / ASSET_CHUNK_INDEX_MULTIPLIER) as u32,
+            assets_type: Some(
+                ((position % ASSET_CHUNK_INDEX_MULTIPLIER) / ASSET_TYPE_MULTIPLIER) as u16,
+            ),
+            index_in_chunk: (position % ASSET_TYPE_MULTIPLIER) as u32,
+        }
+    }
+
+    /// Decodes event_index from near_balance_events.
+    /// `block_timestamp_nanos` has millisecond precision, see `TIMESTAMP_MULTIPLIER`
+    pub(crate) fn balance_parts(&self) -> EventIndexParts {
+        let position = self.0 % TIMESTAMP_MULTIPLIER;
+        EventIndexParts {
+            block_timestamp_nanos: self.timestamp_nanos().into(),
+            chunk_index: (position / BALANCE_CHUNK_INDEX_MULTIPLIER) as u32,
+            assets_type: None,
+            index_in_chunk: (position % BALANCE_CHUNK_INDEX_MULTIPLIER) as u32,
+        }
+    }
+
+    /// Decodes event_index built by `from_shard_event_parts`, `chunk_index` is the shard_id
+    pub(crate) fn shard_event_parts(&self) -> EventIndexParts {
+        let position = self.0 % TIMESTAMP_MULTIPLIER;
+        EventIndexParts {
+            block_timestamp_nanos: self.timestamp_nanos().into(),
+            chunk_index: (position / SHARD_EVENT_SHARD_ID_MULTIPLIER) as u32,
+            assets_type: None,
+            index_in_chunk: (position % SHARD_EVENT_SHARD_ID_MULTIPLIER) as u32,
+        }
+    }
+
+    /// Decodes event_index built by `from_action_parts`
+    pub(crate) fn action_parts(&self) -> ActionIndexParts {
+        let position = self.0 % TIMESTAMP_MULTIPLIER;
+        ActionIndexParts {
+            timestamp_nanos: self.timestamp_nanos(),
+            shard_id: (position / ACTION_SHARD_ID_MULTIPLIER) as u32,
+            index_in_chunk: ((position % ACTION_SHARD_ID_MULTIPLIER)
+                / ACTION_INDEX_IN_CHUNK_MULTIPLIER) as u32,
+            index_in_action_receipt: (position % ACTION_INDEX_IN_CHUNK_MULTIPLIER) as u32,
+        }
+    }
+}
+
+impl From<EventIndex> for u128 {
+    fn from(event_index: EventIndex) -> Self {
+        event_index.0
+    }
+}
+
+impl std::fmt::Display for EventIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balance_event_index_roundtrip() {
+        let event_index = EventIndex::new(16708676800272181160000000010000005);
+        let parts = event_index.balance_parts();
+        assert_eq!(parts.block_timestamp_nanos.0, 1670867680027218116);
+        assert_eq!(parts.chunk_index, 10);
+        assert_eq!(parts.assets_type, None);
+        assert_eq!(parts.index_in_chunk, 5);
+        assert_eq!(
+            EventIndex::from_balance_parts(1670867680027218116, 10, 5).unwrap(),
+            event_index
+        );
+    }
+
+    #[test]
+    fn test_asset_event_index_roundtrip() {
+        let event_index = EventIndex::new(16565042430000000000000000030070027);
+        let parts = event_index.asset_parts();
+        assert_eq!(parts.block_timestamp_nanos.0, 1656504243000000000);
+        assert_eq!(parts.chunk_index, 3);
+        assert_eq!(parts.assets_type, Some(7));
+        assert_eq!(parts.index_in_chunk, 27);
+        assert_eq!(
+            EventIndex::from_asset_parts(1656504243000000000, 3, 7, 27).unwrap(),
+            event_index
+        );
+    }
+
+    #[test]
+    fn test_action_event_index_roundtrip() {
+        let parts = ActionIndexParts {
+            timestamp_nanos: 1670867680027218116,
+            shard_id: 3,
+            // busy chunks have thousands of receipts
+            index_in_chunk: 1_234_567,
+            index_in_action_receipt: 99,
+        };
+        let event_index = EventIndex::from_action_parts(parts).unwrap();
+        assert_eq!(u128::from(event_index), 16708676800272181160030001234567099);
+        assert_eq!(event_index.action_parts(), parts);
+        assert!(EventIndex::from_action_parts(ActionIndexParts {
+            index_in_chunk: i32::MAX as u32,
+            ..parts
+        })
+        .is_ok());
+        assert!(EventIndex::from_action_parts(ActionIndexParts {
+            index_in_action_receipt: 1000,
+            ..parts
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_shard_event_index_roundtrip() {
+        // the index in the shard does not fit into the asset layout
+        let event_index =
+            EventIndex::from_shard_event_parts(1670867680027218116, 2, 54_321).unwrap();
+        assert_eq!(u128::from(event_index), 16708676800272181160000020000054321);
+        let parts = event_index.shard_event_parts();
+        assert_eq!(parts.block_timestamp_nanos.0, 1670867680027218116);
+        assert_eq!(parts.chunk_index, 2);
+        assert_eq!(parts.assets_type, None);
+        assert_eq!(parts.index_in_chunk, 54_321);
+        assert!(
+            EventIndex::from_shard_event_parts(1670867680027218116, 2, i32::MAX as u32).is_ok()
+        );
+        assert!(EventIndex::from_shard_event_parts(1670867680027218116, 1_000_000, 0).is_err());
+    }
+
+    #[test]
+    fn test_event_index_from_timestamp() {
+        let event_index = EventIndex::from_timestamp(1670867692546051383);
+        assert_eq!(u128::from(event_index), 16708676925460513830000000000000000);
+        assert_eq!(event_index.timestamp_nanos(), 1670867692546051383);
+    }
+
+    #[test]
+    fn test_event_index_validation() {
+        assert!(EventIndex::checked_from(16708676458550339330000000000000003).is_ok());
+        // block height instead of event_index
+        assert!(EventIndex::checked_from(80500000).is_err());
+        // timestamp instead of event_index
+        assert!(EventIndex::checked_from(1670867692546051383).is_err());
+        assert!(EventIndex::checked_from(u128::MAX).is_err());
+    }
+
+    #[test]
+    fn test_event_index_encoding_overflow() {
+        assert!(EventIndex::from_asset_parts(1656504243000000000, 3, 1000, 27).is_err());
+        assert!(EventIndex::from_balance_parts(1656504243000000000, 3, 1_000_000).is_err());
+    }
+}
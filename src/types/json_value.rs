@@ -0,0 +1,19 @@
+use paperclip::v2::{models::DataType, schema::TypedData};
+use serde::{Deserialize, Serialize};
+
+/// Arbitrary JSON taken from the contracts or from the DB (e.g. action arguments)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonValue(pub serde_json::Value);
+
+impl From<serde_json::Value> for JsonValue {
+    fn from(value: serde_json::Value) -> Self {
+        Self(value)
+    }
+}
+
+impl TypedData for JsonValue {
+    fn data_type() -> DataType {
+        DataType::Object
+    }
+}
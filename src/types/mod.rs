@@ -1,7 +1,12 @@
 pub(crate) mod account_id;
+pub(crate) mod event_index;
+pub(crate) mod json_value;
 pub(crate) mod numeric;
 pub(crate) mod pagoda_api_key;
 pub mod query_params;
+pub(crate) mod timestamp;
 
 pub(crate) use account_id::AccountId;
+pub(crate) use event_index::{ActionIndexParts, EventIndex, EventIndexParts};
+pub(crate) use json_value::JsonValue;
 pub(crate) use numeric::{U128, U64};
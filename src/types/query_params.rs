@@ -1,22 +1,61 @@
-use crate::{errors, types};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use once_cell::sync::Lazy;
 use paperclip::actix::Apiv2Schema;
 
-const DEFAULT_PAGE_LIMIT: u32 = 20;
-const MAX_PAGE_LIMIT: u32 = 100;
+use crate::{config, errors, types};
+
+static DEFAULT_PAGE_LIMIT: Lazy<AtomicU32> =
+    Lazy::new(|| AtomicU32::new(config::LimitsConfig::default().default_page_limit));
+static MAX_PAGE_LIMIT: Lazy<AtomicU32> =
+    Lazy::new(|| AtomicU32::new(config::LimitsConfig::default().max_page_limit));
+
+/// Should be called once at startup, before the server starts
+pub(crate) fn init(limits_config: &config::LimitsConfig) {
+    DEFAULT_PAGE_LIMIT.store(limits_config.default_page_limit, Ordering::Relaxed);
+    MAX_PAGE_LIMIT.store(limits_config.max_page_limit, Ordering::Relaxed);
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
 pub struct BlockParams {
     pub block_timestamp_nanos: Option<types::U64>,
     pub block_height: Option<types::U64>,
+    pub block_hash: Option<String>,
+    /// ISO 8601 datetime, e.g. `2024-12-31T23:59:59Z`. UTC is used if the timezone is not specified
+    pub at: Option<String>,
+    /// `optimistic` by default
+    pub finality: Option<Finality>,
+}
+
+/// `final` blocks can't be reverted, `optimistic` is the latest block we have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+#[serde(rename_all = "lowercase")]
+pub enum Finality {
+    Final,
+    Optimistic,
+}
+
+impl Default for Finality {
+    fn default() -> Self {
+        Self::Optimistic
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
 pub struct PaginationParams {
-    /// Maximum available limit 100
+    /// Maximum available limit is `limits.max_page_limit` from the server config (100 by default)
     pub limit: Option<u32>,
     pub after_event_index: Option<types::U128>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
+pub struct HistoryParams {
+    /// Add decoded `event_index` to each history item
+    pub with_event_index_parts: Option<bool>,
+    /// Do not look up the transaction for each history item. Makes the response faster
+    pub skip_transaction_info: Option<bool>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Apiv2Schema)]
 pub struct LimitParams {
     pub limit: Option<u32>,
@@ -31,16 +70,17 @@ pub(crate) struct Pagination {
 
 /// Validates limit_param received from the user, sets the default value if none was provided
 pub(crate) fn checked_get_limit(limit_param: Option<u32>) -> crate::Result<u32> {
+    let max_page_limit = MAX_PAGE_LIMIT.load(Ordering::Relaxed);
     Ok(if let Some(limit) = limit_param {
-        if limit > MAX_PAGE_LIMIT || limit == 0 {
+        if limit > max_page_limit || limit == 0 {
             return Err(errors::ErrorKind::InvalidInput(format!(
                 "Limit should be in range [1, {}]",
-                MAX_PAGE_LIMIT
+                max_page_limit
             ))
             .into());
         }
         limit
     } else {
-        DEFAULT_PAGE_LIMIT
+        DEFAULT_PAGE_LIMIT.load(Ordering::Relaxed)
     })
 }
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Deserializer};
+
+const NANOS_IN_SECOND: i64 = 1_000_000_000;
+const NANOS_IN_MILLISECOND: u64 = 1_000_000;
+
+/// Converts the datetime given by the contract to the unix timestamp in nanoseconds.
+/// We support ISO 8601 datetimes (the timezone is UTC if it's not specified)
+/// and unix timestamps in milliseconds, which are widely used by the contracts.
+/// Returns None if the value could not be parsed or it's before 1970
+pub(crate) fn parse_to_nanos(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+        return value.parse::<u64>().ok()?.checked_mul(NANOS_IN_MILLISECOND);
+    }
+    parse_iso_to_nanos(value)
+}
+
+/// Converts ISO 8601 datetime to the unix timestamp in nanoseconds, UTC is used if the timezone
+/// is not specified. Used for the user input, where the bare number is ambiguous
+pub(crate) fn parse_iso_to_nanos(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let datetime = if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(value) {
+        datetime.with_timezone(&chrono::Utc)
+    } else {
+        let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+            .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f"))
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .ok()?
+                    .and_hms_opt(0, 0, 0)
+            })?;
+        chrono::TimeZone::from_utc_datetime(&chrono::Utc, &naive)
+    };
+    let nanos = datetime
+        .timestamp()
+        .checked_mul(NANOS_IN_SECOND)?
+        .checked_add(datetime.timestamp_subsec_nanos() as i64)?;
+    u64::try_from(nanos).ok()
+}
+
+/// Contracts are expected to store the timestamps as strings, but some of them use numbers.
+/// We keep both, so that the whole metadata does not fail to deserialize
+pub(crate) fn deserialize_string_or_number<'de, D>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(value)) => Some(value),
+            Some(serde_json::Value::Number(value)) => Some(value.to_string()),
+            _ => None,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_to_nanos() {
+        let expected = Some(1_656_633_600_000_000_000);
+        assert_eq!(parse_to_nanos("1656633600000"), expected);
+        assert_eq!(parse_to_nanos("2022-07-01T00:00:00Z"), expected);
+        assert_eq!(parse_to_nanos("2022-07-01T03:00:00+03:00"), expected);
+        assert_eq!(parse_to_nanos("2022-07-01T00:00:00"), expected);
+        assert_eq!(parse_to_nanos("2022-07-01 00:00:00"), expected);
+        assert_eq!(parse_to_nanos(" 2022-07-01 "), expected);
+        assert_eq!(
+            parse_to_nanos("2022-07-01T00:00:00.5Z"),
+            Some(1_656_633_600_500_000_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_iso_to_nanos() {
+        assert_eq!(
+            parse_iso_to_nanos("2022-07-01T00:00:00Z"),
+            Some(1_656_633_600_000_000_000)
+        );
+        assert_eq!(
+            parse_iso_to_nanos("2022-07-01"),
+            Some(1_656_633_600_000_000_000)
+        );
+        assert_eq!(parse_iso_to_nanos("1656633600000"), None);
+        assert_eq!(parse_iso_to_nanos("yesterday"), None);
+    }
+
+    #[test]
+    fn test_parse_to_nanos_invalid() {
+        assert_eq!(parse_to_nanos(""), None);
+        assert_eq!(parse_to_nanos("yesterday"), None);
+        assert_eq!(parse_to_nanos("-1656633600000"), None);
+        assert_eq!(parse_to_nanos("1960-01-01T00:00:00Z"), None);
+        assert_eq!(parse_to_nanos("99999999999999999999"), None);
+    }
+}